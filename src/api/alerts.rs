@@ -0,0 +1,320 @@
+use crate::models::{time, PlurkUser};
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub(crate) const ALERTS_GET_ACTIVE_URL: &str = "/APP/Alerts/getActive";
+pub(crate) const ALERTS_GET_HISTORY_URL: &str = "/APP/Alerts/getHistory";
+
+plurk_endpoint! {
+    const ALERTS_ADD_AS_FAN_URL = "/APP/Alerts/addAsFan";
+    /// Answer the friendship request of `user_id` by making them a fan.
+    pub async fn add_as_fan(user_id: u64);
+
+    const ALERTS_ADD_AS_FRIEND_URL = "/APP/Alerts/addAsFriend";
+    /// Accept the friendship request of `user_id`.
+    pub async fn add_as_friend(user_id: u64);
+
+    const ALERTS_ADD_ALL_AS_FAN_URL = "/APP/Alerts/addAllAsFan";
+    /// Make everyone with a pending friendship request a fan.
+    pub async fn add_all_as_fan();
+
+    const ALERTS_ADD_ALL_AS_FRIENDS_URL = "/APP/Alerts/addAllAsFriends";
+    /// Accept every pending friendship request.
+    pub async fn add_all_as_friends();
+
+    const ALERTS_DENY_FRIENDSHIP_URL = "/APP/Alerts/denyFriendship";
+    pub async fn deny_friendship(user_id: u64);
+
+    const ALERTS_REMOVE_NOTIFICATION_URL = "/APP/Alerts/removeNotification";
+    /// Dismiss the alert about `user_id`, e.g. a new fan.
+    pub async fn remove_notification(user_id: u64);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertKind {
+    FriendshipRequest {
+        from_user: PlurkUser,
+    },
+    FriendshipPending {
+        to_user: PlurkUser,
+    },
+    NewFan {
+        new_fan: PlurkUser,
+    },
+    FriendshipAccepted {
+        friend_info: PlurkUser,
+    },
+    NewFriend {
+        new_friend: PlurkUser,
+    },
+    PrivatePlurk {
+        owner: PlurkUser,
+        plurk_id: u64,
+    },
+    PlurkLiked {
+        from_user: PlurkUser,
+        plurk_id: u64,
+        #[serde(default)]
+        num_others: u64,
+    },
+    PlurkReplurked {
+        from_user: PlurkUser,
+        plurk_id: u64,
+        #[serde(default)]
+        num_others: u64,
+    },
+    Mentioned {
+        from_user: PlurkUser,
+        plurk_id: u64,
+        #[serde(default)]
+        num_others: u64,
+        #[serde(default)]
+        response_id: Option<u64>,
+    },
+    MyResponded {
+        from_user: PlurkUser,
+        plurk_id: u64,
+        #[serde(default)]
+        response_id: Option<u64>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+impl AlertKind {
+    /// The user who triggered the alert.
+    pub fn user(&self) -> Option<&PlurkUser> {
+        match self {
+            Self::FriendshipRequest { from_user }
+            | Self::PlurkLiked { from_user, .. }
+            | Self::PlurkReplurked { from_user, .. }
+            | Self::Mentioned { from_user, .. }
+            | Self::MyResponded { from_user, .. } => Some(from_user),
+            Self::FriendshipPending { to_user } => Some(to_user),
+            Self::NewFan { new_fan } => Some(new_fan),
+            Self::FriendshipAccepted { friend_info } => Some(friend_info),
+            Self::NewFriend { new_friend } => Some(new_friend),
+            Self::PrivatePlurk { owner, .. } => Some(owner),
+            Self::Unknown => None,
+        }
+    }
+
+    pub fn plurk_id(&self) -> Option<u64> {
+        match self {
+            Self::PrivatePlurk { plurk_id, .. }
+            | Self::PlurkLiked { plurk_id, .. }
+            | Self::PlurkReplurked { plurk_id, .. }
+            | Self::Mentioned { plurk_id, .. }
+            | Self::MyResponded { plurk_id, .. } => Some(*plurk_id),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.user().map(PlurkUser::name).unwrap_or_default();
+        match self {
+            Self::FriendshipRequest { .. } => write!(f, "{} wants to be your friend", name),
+            Self::FriendshipPending { .. } => write!(f, "Friend request to {} is pending", name),
+            Self::NewFan { .. } => write!(f, "{} became your fan", name),
+            Self::FriendshipAccepted { .. } => write!(f, "{} accepted your friend request", name),
+            Self::NewFriend { .. } => write!(f, "{} is now your friend", name),
+            Self::PrivatePlurk { .. } => write!(f, "{} shared a private plurk with you", name),
+            Self::PlurkLiked { .. } => write!(f, "{} liked your plurk", name),
+            Self::PlurkReplurked { .. } => write!(f, "{} replurked your plurk", name),
+            Self::Mentioned { .. } => write!(f, "{} mentioned you", name),
+            Self::MyResponded { .. } => write!(f, "{} responded to your plurk", name),
+            Self::Unknown => write!(f, "Unknown alert"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Alert {
+    #[serde(flatten)]
+    pub kind: AlertKind,
+    #[serde(with = "time::plurk_date")]
+    pub posted: DateTime<Utc>,
+}
+
+/// Paging state of [`Plurk::alert_history_stream`].
+struct HistoryState {
+    offset: Option<DateTime<Utc>>,
+    /// Alerts of the last page posted in its oldest second, which the next page repeats.
+    /// Alerts have no id, so they are told apart by their whole content.
+    seen: Vec<Alert>,
+}
+
+/// Split a `getHistory` page into the alerts not returned before and the state for the
+/// next page, which holds alerts strictly older than its offset.
+///
+/// Timestamps only have second precision, so the next page starts one second after the
+/// oldest alert to pick up others posted in the same second. When a page brought nothing
+/// new, step past that second instead, otherwise the same page would be fetched forever.
+fn history_page(
+    state: &HistoryState,
+    alerts: Vec<Alert>,
+    limit: usize,
+) -> (Vec<Alert>, Option<HistoryState>) {
+    let page_len = alerts.len();
+    let Some(oldest) = alerts.iter().map(|alert| alert.posted).min() else {
+        return (alerts, None);
+    };
+    let seen = alerts
+        .iter()
+        .filter(|alert| alert.posted == oldest)
+        .cloned()
+        .collect();
+    let fresh: Vec<Alert> = alerts
+        .into_iter()
+        .filter(|alert| !state.seen.contains(alert))
+        .collect();
+
+    let offset = if page_len < limit {
+        None
+    } else if fresh.is_empty() {
+        Some(oldest)
+    } else {
+        Some(oldest + Duration::seconds(1))
+    };
+    let next = offset.map(|offset| HistoryState {
+        offset: Some(offset),
+        seen,
+    });
+    (fresh, next)
+}
+
+impl Plurk {
+    /// Alerts that still need an action, e.g. pending friendship requests.
+    pub async fn get_active_alerts(&self) -> Result<Vec<Alert>, PlurkError> {
+        self.call(ALERTS_GET_ACTIVE_URL, None::<()>).await
+    }
+
+    /// Fetch one page of alert history, older than `offset` when given.
+    pub async fn get_alert_history(
+        &self,
+        offset: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<Alert>, PlurkError> {
+        let mut query = vec![("limit", limit.to_string())];
+        if let Some(offset) = offset {
+            query.push(("offset", time::format_offset(&offset)));
+        }
+        self.call(ALERTS_GET_HISTORY_URL, Some(query)).await
+    }
+
+    /// Stream the whole alert history, newest first, `limit` alerts per request.
+    pub fn alert_history_stream(
+        &self,
+        limit: usize,
+    ) -> impl Stream<Item = Result<Alert, PlurkError>> + '_ {
+        let init = HistoryState {
+            offset: None,
+            seen: Vec::new(),
+        };
+        stream::try_unfold(Some(init), move |state| async move {
+            let Some(state) = state else {
+                return Ok::<_, PlurkError>(None);
+            };
+            let alerts = self.get_alert_history(state.offset, limit).await?;
+            let (alerts, next) = history_page(&state, alerts, limit);
+            Ok(Some((stream::iter(alerts.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const HISTORY: &str = r#"[
+        {
+            "type": "plurk_liked",
+            "from_user": {"id": 3, "nick_name": "alvin", "display_name": "amix"},
+            "plurk_id": 42,
+            "num_others": 2,
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT"
+        },
+        {
+            "type": "new_fan",
+            "new_fan": {"id": 4, "nick_name": "bob"},
+            "posted": "Thu, 04 Jun 2009 10:00:00 GMT"
+        },
+        {
+            "type": "something_new",
+            "posted": "Wed, 03 Jun 2009 10:00:00 GMT"
+        }
+    ]"#;
+
+    #[test]
+    fn test_deserialize_alerts() {
+        let alerts: Vec<Alert> = serde_json::from_str(HISTORY).unwrap();
+        assert_eq!(alerts.len(), 3);
+        assert_eq!(alerts[0].kind.plurk_id(), Some(42));
+        assert_eq!(alerts[0].kind.to_string(), "amix liked your plurk");
+        assert_eq!(alerts[1].kind.to_string(), "bob became your fan");
+        assert_eq!(alerts[2].kind, AlertKind::Unknown);
+        assert_eq!(alerts[2].kind.user(), None);
+    }
+
+    fn alert(plurk_id: u64, posted: &str) -> Alert {
+        let raw = format!(
+            r#"{{"type": "plurk_liked", "from_user": {{"id": 3, "nick_name": "alvin"}},
+                "plurk_id": {}, "posted": "{}"}}"#,
+            plurk_id, posted
+        );
+        serde_json::from_str(&raw).unwrap()
+    }
+
+    #[test]
+    fn test_history_page() {
+        let ids = |alerts: &[Alert]| -> Vec<u64> {
+            alerts.iter().filter_map(|a| a.kind.plurk_id()).collect()
+        };
+        let at = |s| Utc.with_ymd_and_hms(2009, 6, 5, 10, 0, s).unwrap();
+        let state = HistoryState {
+            offset: None,
+            seen: Vec::new(),
+        };
+        assert!(history_page(&state, Vec::new(), 2).1.is_none());
+
+        // Two alerts share 10:00:00, the oldest second of the first page.
+        let first = vec![
+            alert(1, "Fri, 05 Jun 2009 10:00:01 GMT"),
+            alert(2, "Fri, 05 Jun 2009 10:00:00 GMT"),
+        ];
+        let (alerts, state) = history_page(&state, first, 2);
+        assert_eq!(ids(&alerts), [1, 2]);
+        let state = state.unwrap();
+        assert_eq!(state.offset, Some(at(1)));
+
+        let second = vec![
+            alert(2, "Fri, 05 Jun 2009 10:00:00 GMT"),
+            alert(3, "Fri, 05 Jun 2009 10:00:00 GMT"),
+        ];
+        let (alerts, state) = history_page(&state, second.clone(), 2);
+        assert_eq!(ids(&alerts), [3]);
+        let state = state.unwrap();
+        assert_eq!(state.offset, Some(at(1)));
+
+        let (alerts, state) = history_page(&state, second, 2);
+        assert!(alerts.is_empty());
+        assert_eq!(state.unwrap().offset, Some(at(0)));
+
+        let last = vec![alert(4, "Fri, 05 Jun 2009 09:00:00 GMT")];
+        let state = HistoryState {
+            offset: Some(at(0)),
+            seen: Vec::new(),
+        };
+        let (alerts, state) = history_page(&state, last, 2);
+        assert_eq!(ids(&alerts), [4]);
+        assert!(state.is_none());
+    }
+}
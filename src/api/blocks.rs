@@ -0,0 +1,45 @@
+use crate::models::PlurkUser;
+use crate::plurk::{Plurk, PlurkError};
+use serde::Deserialize;
+
+pub(crate) const BLOCKS_GET_URL: &str = "/APP/Blocks/get";
+
+plurk_endpoint! {
+    const BLOCKS_BLOCK_URL = "/APP/Blocks/block";
+    /// Block `user_id`, also removing them as friend and fan.
+    pub async fn block(user_id: u64);
+
+    const BLOCKS_UNBLOCK_URL = "/APP/Blocks/unblock";
+    pub async fn unblock(user_id: u64);
+}
+
+/// A page of blocked users, see [`Plurk::get_blocks`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Blocks {
+    /// Number of blocked users over all pages.
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub users: Vec<PlurkUser>,
+}
+
+impl Plurk {
+    /// Blocked users, starting at `offset`.
+    pub async fn get_blocks(&self, offset: usize) -> Result<Blocks, PlurkError> {
+        self.call(BLOCKS_GET_URL, Some([("offset", offset.to_string())]))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_blocks() {
+        let raw = r#"{"total": 12, "users": [{"id": 3, "nick_name": "alvin"}]}"#;
+        let blocks: Blocks = serde_json::from_str(raw).unwrap();
+        assert_eq!(blocks.total, 12);
+        assert_eq!(blocks.users[0].id, 3);
+    }
+}
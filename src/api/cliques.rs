@@ -0,0 +1,78 @@
+use crate::models::{LimitedTo, PlurkUser};
+use crate::plurk::{Plurk, PlurkError};
+
+pub(crate) const CLIQUES_GET_CLIQUES_URL: &str = "/APP/Cliques/getCliques";
+pub(crate) const CLIQUES_GET_CLIQUE_URL: &str = "/APP/Cliques/getClique";
+
+plurk_endpoint! {
+    const CLIQUES_CREATE_CLIQUE_URL = "/APP/Cliques/createClique";
+    pub async fn create_clique(clique_name: &str);
+
+    const CLIQUES_RENAME_CLIQUE_URL = "/APP/Cliques/renameClique";
+    pub async fn rename_clique(clique_name: &str, new_name: &str);
+
+    const CLIQUES_ADD_URL = "/APP/Cliques/add";
+    /// Add `user_id`, who must be a friend, to the clique.
+    pub async fn add_to_clique(clique_name: &str, user_id: u64);
+
+    const CLIQUES_REMOVE_URL = "/APP/Cliques/remove";
+    pub async fn remove_from_clique(clique_name: &str, user_id: u64);
+}
+
+/// A named group of friends, see [`Plurk::get_clique`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clique {
+    pub name: String,
+    pub users: Vec<PlurkUser>,
+}
+
+impl Clique {
+    /// Ids of the members. Pass `&clique` to
+    /// [`PlurkAddBuilder::limited_to`](crate::api::timeline::PlurkAddBuilder::limited_to)
+    /// to post to them.
+    pub fn user_ids(&self) -> Vec<u64> {
+        self.users.iter().map(|u| u.id).collect()
+    }
+}
+
+impl From<&Clique> for LimitedTo {
+    fn from(clique: &Clique) -> Self {
+        Self::Users(clique.user_ids())
+    }
+}
+
+impl Plurk {
+    /// Names of the user's cliques.
+    pub async fn get_cliques(&self) -> Result<Vec<String>, PlurkError> {
+        self.call(CLIQUES_GET_CLIQUES_URL, None::<()>).await
+    }
+
+    pub async fn get_clique(&self, clique_name: &str) -> Result<Clique, PlurkError> {
+        let users = self
+            .call(
+                CLIQUES_GET_CLIQUE_URL,
+                Some([("clique_name", clique_name.to_string())]),
+            )
+            .await?;
+        Ok(Clique {
+            name: clique_name.to_string(),
+            users,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clique_user_ids() {
+        let raw = r#"[{"id": 3, "nick_name": "alvin"}, {"id": 5, "nick_name": "bob"}]"#;
+        let clique = Clique {
+            name: String::from("close"),
+            users: serde_json::from_str(raw).unwrap(),
+        };
+        assert_eq!(clique.user_ids(), [3, 5]);
+        assert_eq!(LimitedTo::from(&clique), LimitedTo::Users(vec![3, 5]));
+    }
+}
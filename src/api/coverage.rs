@@ -0,0 +1,149 @@
+use super::{
+    alerts::{
+        ALERTS_ADD_ALL_AS_FAN_URL, ALERTS_ADD_ALL_AS_FRIENDS_URL, ALERTS_ADD_AS_FAN_URL,
+        ALERTS_ADD_AS_FRIEND_URL, ALERTS_DENY_FRIENDSHIP_URL, ALERTS_GET_ACTIVE_URL,
+        ALERTS_GET_HISTORY_URL, ALERTS_REMOVE_NOTIFICATION_URL,
+    },
+    blocks::{BLOCKS_BLOCK_URL, BLOCKS_GET_URL, BLOCKS_UNBLOCK_URL},
+    cliques::{
+        CLIQUES_ADD_URL, CLIQUES_CREATE_CLIQUE_URL, CLIQUES_GET_CLIQUES_URL,
+        CLIQUES_GET_CLIQUE_URL, CLIQUES_REMOVE_URL, CLIQUES_RENAME_CLIQUE_URL,
+    },
+    emoticons::EMOTICONS_GET_URL,
+    friends_fans::{
+        FRIENDS_FANS_BECOME_FAN_URL, FRIENDS_FANS_BECOME_FRIEND_URL,
+        FRIENDS_FANS_GET_COMPLETION_URL, FRIENDS_FANS_GET_FANS_BY_OFFSET_URL,
+        FRIENDS_FANS_GET_FOLLOWING_BY_OFFSET_URL, FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL,
+        FRIENDS_FANS_REMOVE_AS_FRIEND_URL, FRIENDS_FANS_SET_FOLLOWING_URL,
+    },
+    oauth::{CHECK_TIME_URL, CHECK_TOKEN_URL, ECHO_URL, EXPIRE_TOKEN_URL},
+    plurk_top::{PLURK_TOP_GET_COLLECTIONS_URL, PLURK_TOP_GET_PLURKS_URL},
+    polling::{POLLING_GET_PLURKS_URL, POLLING_GET_UNREAD_COUNT_URL},
+    profile::{PROFILE_GET_OWN_PROFILE_URL, PROFILE_GET_PUBLIC_PROFILE_URL},
+    realtime::REALTIME_GET_USER_CHANNEL_URL,
+    responses::{RESPONSES_GET_URL, RESPONSES_RESPONSE_ADD_URL, RESPONSES_RESPONSE_DELETE_URL},
+    search::{PLURK_SEARCH_URL, USER_SEARCH_URL},
+    timeline::{
+        TIMELINE_FAVORITE_PLURKS_URL, TIMELINE_GET_PLURKS_URL, TIMELINE_GET_PLURK_URL,
+        TIMELINE_GET_UNREAD_PLURKS_URL, TIMELINE_MARK_AS_READ_URL, TIMELINE_MUTE_PLURKS_URL,
+        TIMELINE_PLURK_ADD_URL, TIMELINE_PLURK_DELETE_URL, TIMELINE_PLURK_EDIT_URL,
+        TIMELINE_REPLURK_URL, TIMELINE_TOGGLE_COMMENTS_URL, TIMELINE_UNFAVORITE_PLURKS_URL,
+        TIMELINE_UNMUTE_PLURKS_URL, TIMELINE_UNREPLURK_URL, TIMELINE_UPLOAD_PICTURE_URL,
+    },
+    users::{USERS_GET_KARMA_STATS_URL, USERS_ME_URL, USERS_UPDATE_AVATAR_URL, USERS_UPDATE_URL},
+};
+
+/// An endpoint of the Plurk API 2.0 and the function wrapping it, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Endpoint {
+    pub path: &'static str,
+    pub method: &'static str,
+    /// Typed wrapper on [`Plurk`](crate::plurk::Plurk); `None` when the endpoint is
+    /// only reachable through [`Plurk::request`](crate::plurk::Plurk::request).
+    pub wrapper: Option<&'static str>,
+}
+
+const fn wrapped(path: &'static str, wrapper: &'static str) -> Endpoint {
+    Endpoint {
+        path,
+        method: "POST",
+        wrapper: Some(wrapper),
+    }
+}
+
+const fn missing(path: &'static str) -> Endpoint {
+    Endpoint {
+        path,
+        method: "POST",
+        wrapper: None,
+    }
+}
+
+const ENDPOINTS: &[Endpoint] = &[
+    wrapped(USERS_ME_URL, "me"),
+    wrapped(USERS_UPDATE_URL, "update_user"),
+    wrapped(USERS_UPDATE_AVATAR_URL, "update_avatar"),
+    wrapped(USERS_GET_KARMA_STATS_URL, "get_karma_stats"),
+    wrapped(PROFILE_GET_OWN_PROFILE_URL, "get_own_profile"),
+    wrapped(PROFILE_GET_PUBLIC_PROFILE_URL, "get_public_profile"),
+    wrapped(REALTIME_GET_USER_CHANNEL_URL, "get_user_channel"),
+    wrapped(POLLING_GET_PLURKS_URL, "poll_plurks"),
+    wrapped(POLLING_GET_UNREAD_COUNT_URL, "get_unread_count"),
+    wrapped(TIMELINE_GET_PLURK_URL, "get_plurk"),
+    wrapped(TIMELINE_GET_PLURKS_URL, "get_plurks"),
+    wrapped(TIMELINE_GET_UNREAD_PLURKS_URL, "get_unread_plurks"),
+    missing("/APP/Timeline/getPublicPlurks"),
+    wrapped(TIMELINE_PLURK_ADD_URL, "plurk_add"),
+    wrapped(TIMELINE_PLURK_DELETE_URL, "delete_plurk"),
+    wrapped(TIMELINE_PLURK_EDIT_URL, "edit_plurk"),
+    wrapped(TIMELINE_TOGGLE_COMMENTS_URL, "set_comment_permission"),
+    wrapped(TIMELINE_MUTE_PLURKS_URL, "mute_plurks"),
+    wrapped(TIMELINE_UNMUTE_PLURKS_URL, "unmute_plurks"),
+    wrapped(TIMELINE_FAVORITE_PLURKS_URL, "favorite_plurks"),
+    wrapped(TIMELINE_UNFAVORITE_PLURKS_URL, "unfavorite_plurks"),
+    wrapped(TIMELINE_REPLURK_URL, "replurk"),
+    wrapped(TIMELINE_UNREPLURK_URL, "unreplurk"),
+    wrapped(TIMELINE_MARK_AS_READ_URL, "mark_as_read"),
+    wrapped(TIMELINE_UPLOAD_PICTURE_URL, "upload_picture"),
+    missing("/APP/Timeline/reportAbuse"),
+    wrapped(RESPONSES_GET_URL, "get_responses"),
+    wrapped(RESPONSES_RESPONSE_ADD_URL, "respond"),
+    wrapped(RESPONSES_RESPONSE_DELETE_URL, "delete_response"),
+    wrapped(FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL, "get_friends"),
+    wrapped(FRIENDS_FANS_GET_FANS_BY_OFFSET_URL, "get_fans"),
+    wrapped(FRIENDS_FANS_GET_FOLLOWING_BY_OFFSET_URL, "get_following"),
+    wrapped(FRIENDS_FANS_BECOME_FRIEND_URL, "add_friend"),
+    wrapped(FRIENDS_FANS_REMOVE_AS_FRIEND_URL, "remove_friend"),
+    wrapped(FRIENDS_FANS_BECOME_FAN_URL, "become_fan"),
+    wrapped(FRIENDS_FANS_SET_FOLLOWING_URL, "set_following"),
+    wrapped(FRIENDS_FANS_GET_COMPLETION_URL, "get_completion"),
+    wrapped(ALERTS_GET_ACTIVE_URL, "get_active_alerts"),
+    wrapped(ALERTS_GET_HISTORY_URL, "get_alert_history"),
+    wrapped(ALERTS_ADD_AS_FAN_URL, "add_as_fan"),
+    wrapped(ALERTS_ADD_ALL_AS_FAN_URL, "add_all_as_fan"),
+    wrapped(ALERTS_ADD_ALL_AS_FRIENDS_URL, "add_all_as_friends"),
+    wrapped(ALERTS_ADD_AS_FRIEND_URL, "add_as_friend"),
+    wrapped(ALERTS_DENY_FRIENDSHIP_URL, "deny_friendship"),
+    wrapped(ALERTS_REMOVE_NOTIFICATION_URL, "remove_notification"),
+    wrapped(PLURK_SEARCH_URL, "search_plurks"),
+    wrapped(USER_SEARCH_URL, "search_users"),
+    wrapped(EMOTICONS_GET_URL, "get_emoticons"),
+    wrapped(BLOCKS_GET_URL, "get_blocks"),
+    wrapped(BLOCKS_BLOCK_URL, "block"),
+    wrapped(BLOCKS_UNBLOCK_URL, "unblock"),
+    wrapped(CLIQUES_GET_CLIQUES_URL, "get_cliques"),
+    wrapped(CLIQUES_GET_CLIQUE_URL, "get_clique"),
+    wrapped(CLIQUES_CREATE_CLIQUE_URL, "create_clique"),
+    wrapped(CLIQUES_RENAME_CLIQUE_URL, "rename_clique"),
+    wrapped(CLIQUES_ADD_URL, "add_to_clique"),
+    wrapped(CLIQUES_REMOVE_URL, "remove_from_clique"),
+    wrapped(PLURK_TOP_GET_COLLECTIONS_URL, "get_plurk_top_collections"),
+    wrapped(PLURK_TOP_GET_PLURKS_URL, "plurk_top_stream"),
+    wrapped(CHECK_TOKEN_URL, "check_token"),
+    wrapped(EXPIRE_TOKEN_URL, "expire_token"),
+    wrapped(CHECK_TIME_URL, "check_time"),
+    wrapped(ECHO_URL, "echo"),
+];
+
+/// Every known `/APP/...` endpoint, grouped as in the Plurk API documentation.
+pub fn coverage() -> &'static [Endpoint] {
+    ENDPOINTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_coverage() {
+        let mut paths = HashSet::new();
+        for endpoint in coverage() {
+            assert!(endpoint.path.starts_with("/APP/"), "{}", endpoint.path);
+            assert!(paths.insert(endpoint.path), "duplicate {}", endpoint.path);
+        }
+        assert!(coverage()
+            .iter()
+            .any(|e| e.path == "/APP/Timeline/plurkAdd" && e.wrapper.is_some()));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::plurk::{Plurk, PlurkError};
+use serde_json::Value;
+use std::collections::HashMap;
+use url::Url;
+
+pub(crate) const EMOTICONS_GET_URL: &str = "/APP/Emoticons/get";
+
+/// Collect the `[url, shortcode]` pairs of the emoticon tables.
+///
+/// Plurk groups them by how they are unlocked, e.g. `{"karma": {"25": [[url, code]]}}`;
+/// the grouping is dropped since a shortcode works the same once available.
+fn collect_emoticons(value: &Value, emoticons: &mut HashMap<String, Url>) {
+    match value {
+        Value::Array(items) => match items.as_slice() {
+            [Value::String(url), Value::String(code)] => {
+                if let Ok(url) = Url::parse(url) {
+                    emoticons.insert(code.clone(), url);
+                }
+            }
+            _ => items.iter().for_each(|v| collect_emoticons(v, emoticons)),
+        },
+        Value::Object(groups) => groups
+            .values()
+            .for_each(|v| collect_emoticons(v, emoticons)),
+        _ => {}
+    }
+}
+
+impl Plurk {
+    /// Image URLs of the emoticons available to the user, by shortcode such as `:-))`.
+    pub async fn get_emoticons(&self) -> Result<HashMap<String, Url>, PlurkError> {
+        let tables: Value = self.call(EMOTICONS_GET_URL, None::<()>).await?;
+        let mut emoticons = HashMap::new();
+        collect_emoticons(&tables, &mut emoticons);
+        Ok(emoticons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_emoticons() {
+        let raw = r#"{
+            "karma": {
+                "0": [["https://s.plurk.com/emoticons/platinum/smile.gif", ":-))"]],
+                "25": [["https://s.plurk.com/emoticons/platinum/cool.gif", "(cool)"]]
+            },
+            "recuited": {
+                "10": [["https://s.plurk.com/emoticons/platinum/bigeyes.gif", "(bigeyes)"]]
+            },
+            "custom": [["not a url", "(broken)"]]
+        }"#;
+        let tables: Value = serde_json::from_str(raw).unwrap();
+        let mut emoticons = HashMap::new();
+        collect_emoticons(&tables, &mut emoticons);
+        assert_eq!(emoticons.len(), 3);
+        assert_eq!(
+            emoticons["(cool)"].as_str(),
+            "https://s.plurk.com/emoticons/platinum/cool.gif"
+        );
+        assert!(!emoticons.contains_key("(broken)"));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::models::PlurkLang;
+use crate::plurk::PlurkError;
+
+/// Stable classification of the `error_text` strings returned by Plurk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PlurkApiErrorKind {
+    InvalidAccessToken,
+    RequiresLogin,
+    NoPermissions,
+    InvalidData,
+    ContentEmpty,
+    MustBeFriends,
+    PlurkNotFound,
+    PlurkOwnerNotFound,
+    UserNotFound,
+    InvalidUserId,
+    AntiFloodSameContent,
+    AntiFloodSpamDomain,
+    AntiFloodTooManyNew,
+    Unknown,
+}
+
+impl PlurkApiErrorKind {
+    /// Classify an `error_text`, ignoring case and an OAuth style `40106:` code prefix.
+    pub fn from_error_text(text: &str) -> Self {
+        let text = text.trim();
+        let text = match text.split_once(':') {
+            Some((code, rest)) if code.chars().all(|c| c.is_ascii_digit()) => rest.trim(),
+            _ => text,
+        };
+        match text.to_lowercase().as_str() {
+            "invalid access token" | "invalid token" => Self::InvalidAccessToken,
+            "requires login" => Self::RequiresLogin,
+            "no permissions" | "no permission" => Self::NoPermissions,
+            "invalid data" => Self::InvalidData,
+            "content is empty" => Self::ContentEmpty,
+            "must be friends" => Self::MustBeFriends,
+            "plurk not found" => Self::PlurkNotFound,
+            "plurk owner not found" => Self::PlurkOwnerNotFound,
+            "user not found" => Self::UserNotFound,
+            "invalid user_id" => Self::InvalidUserId,
+            "anti-flood-same-content" => Self::AntiFloodSameContent,
+            "anti-flood-spam-domain" => Self::AntiFloodSpamDomain,
+            "anti-flood-too-many-new" => Self::AntiFloodTooManyNew,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// A message suitable for end users. Available in English and Traditional Chinese;
+    /// `None` for other languages and for [`Self::Unknown`].
+    pub fn message(&self, lang: PlurkLang) -> Option<&'static str> {
+        let (en, zh_tw) = match self {
+            Self::InvalidAccessToken => (
+                "Your authorization has expired. Please sign in again.",
+                "授權已失效，請重新登入。",
+            ),
+            Self::RequiresLogin => ("Please sign in first.", "請先登入。"),
+            Self::NoPermissions => (
+                "You do not have permission to do this.",
+                "你沒有權限執行此操作。",
+            ),
+            Self::InvalidData => ("The request was invalid.", "請求內容無效。"),
+            Self::ContentEmpty => ("The content cannot be empty.", "內容不能是空的。"),
+            Self::MustBeFriends => (
+                "You must be friends to do this.",
+                "必須是好友才能執行此操作。",
+            ),
+            Self::PlurkNotFound => ("The plurk does not exist.", "找不到這則噗。"),
+            Self::PlurkOwnerNotFound => {
+                ("The owner of the plurk does not exist.", "找不到噗的主人。")
+            }
+            Self::UserNotFound => ("The user does not exist.", "找不到這位使用者。"),
+            Self::InvalidUserId => ("The user id is invalid.", "使用者 ID 無效。"),
+            Self::AntiFloodSameContent => (
+                "You already posted the same content recently.",
+                "你最近已發過相同的內容。",
+            ),
+            Self::AntiFloodSpamDomain => (
+                "The content contains a link that is blocked as spam.",
+                "內容包含被視為垃圾訊息的連結。",
+            ),
+            Self::AntiFloodTooManyNew => (
+                "You are posting too fast. Please try again later.",
+                "發噗太頻繁，請稍後再試。",
+            ),
+            Self::Unknown => return None,
+        };
+        match lang {
+            PlurkLang::English => Some(en),
+            PlurkLang::TraditionalChinese => Some(zh_tw),
+            _ => None,
+        }
+    }
+}
+
+impl PlurkError {
+    /// Classification of the `error_text` carried by a [`PlurkError::Api`].
+    pub fn api_error_kind(&self) -> Option<PlurkApiErrorKind> {
+        match self.inner() {
+            Self::Api { text, .. } => Some(PlurkApiErrorKind::from_error_text(text)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_error_text() {
+        use PlurkApiErrorKind::*;
+        assert_eq!(
+            PlurkApiErrorKind::from_error_text("Plurk not found"),
+            PlurkNotFound
+        );
+        assert_eq!(
+            PlurkApiErrorKind::from_error_text("40106:invalid access token"),
+            InvalidAccessToken
+        );
+        assert_eq!(
+            PlurkApiErrorKind::from_error_text("anti-flood-same-content"),
+            AntiFloodSameContent
+        );
+        assert_eq!(PlurkApiErrorKind::from_error_text("Something new"), Unknown);
+    }
+
+    #[test]
+    fn test_message() {
+        let kind = PlurkApiErrorKind::NoPermissions;
+        assert_eq!(
+            kind.message(PlurkLang::English),
+            Some("You do not have permission to do this.")
+        );
+        assert_eq!(
+            kind.message(PlurkLang::TraditionalChinese),
+            Some("你沒有權限執行此操作。")
+        );
+        assert_eq!(kind.message(PlurkLang::Japanese), None);
+        assert_eq!(PlurkApiErrorKind::Unknown.message(PlurkLang::English), None);
+    }
+
+    #[test]
+    fn test_api_error_kind() {
+        let e = PlurkError::Api {
+            code: 400,
+            text: String::from("Must be friends"),
+        };
+        assert_eq!(e.api_error_kind(), Some(PlurkApiErrorKind::MustBeFriends));
+        let e = PlurkError::Correlated(String::from("abc"), Box::new(e));
+        assert_eq!(e.api_error_kind(), Some(PlurkApiErrorKind::MustBeFriends));
+        let e = PlurkError::APICallError(String::from("Must be friends"));
+        assert_eq!(e.api_error_kind(), None);
+        let e = PlurkError::InvalidArgument(String::from("Must be friends"));
+        assert_eq!(e.api_error_kind(), None);
+    }
+}
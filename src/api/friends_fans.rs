@@ -0,0 +1,233 @@
+use crate::models::PlurkUser;
+use crate::plurk::{Plurk, PlurkError};
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub(crate) const FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL: &str =
+    "/APP/FriendsFans/getFriendsByOffset";
+pub(crate) const FRIENDS_FANS_GET_FANS_BY_OFFSET_URL: &str = "/APP/FriendsFans/getFansByOffset";
+pub(crate) const FRIENDS_FANS_GET_FOLLOWING_BY_OFFSET_URL: &str =
+    "/APP/FriendsFans/getFollowingByOffset";
+pub(crate) const FRIENDS_FANS_GET_COMPLETION_URL: &str = "/APP/FriendsFans/getCompletion";
+pub(crate) const FRIENDS_FANS_SET_FOLLOWING_URL: &str = "/APP/FriendsFans/setFollowing";
+plurk_endpoint! {
+    const FRIENDS_FANS_BECOME_FRIEND_URL = "/APP/FriendsFans/becomeFriend";
+    /// Send a friend request to `friend_id`, or accept theirs.
+    pub async fn add_friend(friend_id: u64);
+
+    const FRIENDS_FANS_REMOVE_AS_FRIEND_URL = "/APP/FriendsFans/removeAsFriend";
+    pub async fn remove_friend(friend_id: u64);
+
+    const FRIENDS_FANS_BECOME_FAN_URL = "/APP/FriendsFans/becomeFan";
+    /// Become a fan of `fan_id`, following their public plurks without being friends.
+    pub async fn become_fan(fan_id: u64);
+}
+
+/// Names of a friend or fan, see [`Plurk::get_completion`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completion {
+    pub nick_name: String,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+/// A user followed by the authorized user, see [`Plurk::get_following`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FollowingUser {
+    #[serde(flatten)]
+    pub user: PlurkUser,
+    /// Whether their plurks show on the timeline; `false` once muted with
+    /// [`Plurk::unfollow`].
+    #[serde(default)]
+    pub following: bool,
+}
+
+/// Largest page `getFriendsByOffset` and `getFansByOffset` return.
+const USERS_PAGE_LIMIT: usize = 100;
+
+/// Offset of the page after one of `page_len` users at `offset`, `None` after the last.
+fn next_users_offset(offset: usize, page_len: usize) -> Option<usize> {
+    (page_len == USERS_PAGE_LIMIT).then_some(offset + page_len)
+}
+
+impl Plurk {
+    async fn get_users_by_offset(
+        &self,
+        url: &'static str,
+        user_id: u64,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<PlurkUser>, PlurkError> {
+        self.call(
+            url,
+            Some([
+                ("user_id", user_id.to_string()),
+                ("offset", offset.to_string()),
+                ("limit", limit.to_string()),
+            ]),
+        )
+        .await
+    }
+
+    fn users_by_offset_stream(
+        &self,
+        url: &'static str,
+        user_id: u64,
+    ) -> impl Stream<Item = Result<PlurkUser, PlurkError>> + '_ {
+        stream::try_unfold(Some(0), move |offset| async move {
+            let Some(offset) = offset else {
+                return Ok::<_, PlurkError>(None);
+            };
+            let page = self
+                .get_users_by_offset(url, user_id, offset, USERS_PAGE_LIMIT)
+                .await?;
+            let next = next_users_offset(offset, page.len());
+            Ok(Some((stream::iter(page.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+
+    /// One page of `user_id`'s friends, starting at `offset`.
+    pub async fn get_friends(
+        &self,
+        user_id: u64,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<PlurkUser>, PlurkError> {
+        self.get_users_by_offset(
+            FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL,
+            user_id,
+            offset,
+            limit,
+        )
+        .await
+    }
+
+    /// Stream all of `user_id`'s friends, fetching them page by page.
+    pub fn get_friends_stream(
+        &self,
+        user_id: u64,
+    ) -> impl Stream<Item = Result<PlurkUser, PlurkError>> + '_ {
+        self.users_by_offset_stream(FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL, user_id)
+    }
+
+    /// One page of `user_id`'s fans, starting at `offset`.
+    pub async fn get_fans(
+        &self,
+        user_id: u64,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<PlurkUser>, PlurkError> {
+        self.get_users_by_offset(FRIENDS_FANS_GET_FANS_BY_OFFSET_URL, user_id, offset, limit)
+            .await
+    }
+
+    /// Stream all of `user_id`'s fans, fetching them page by page.
+    pub fn get_fans_stream(
+        &self,
+        user_id: u64,
+    ) -> impl Stream<Item = Result<PlurkUser, PlurkError>> + '_ {
+        self.users_by_offset_stream(FRIENDS_FANS_GET_FANS_BY_OFFSET_URL, user_id)
+    }
+
+    /// One page of the users the authorized user follows, starting at `offset`, with
+    /// their `following` flags, see [`Self::unfollow`] to change them.
+    pub async fn get_following(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<FollowingUser>, PlurkError> {
+        self.call(
+            FRIENDS_FANS_GET_FOLLOWING_BY_OFFSET_URL,
+            Some([("offset", offset.to_string()), ("limit", limit.to_string())]),
+        )
+        .await
+    }
+
+    /// Names of every friend and fan by user id, to autocomplete `@` mentions.
+    pub async fn get_completion(&self) -> Result<HashMap<u64, Completion>, PlurkError> {
+        self.call(FRIENDS_FANS_GET_COMPLETION_URL, None::<()>).await
+    }
+
+    /// Show `user_id`'s plurks on the timeline. Unlike friendship this needs no approval.
+    pub async fn follow(&self, user_id: u64) -> Result<(), PlurkError> {
+        self.set_following(user_id, true).await
+    }
+
+    /// Stop showing `user_id`'s plurks on the timeline. Friendship, if any, is kept.
+    pub async fn unfollow(&self, user_id: u64) -> Result<(), PlurkError> {
+        self.set_following(user_id, false).await
+    }
+
+    /// Follow or unfollow `user_id`, see [`Self::follow`] and [`Self::unfollow`].
+    pub async fn set_following(&self, user_id: u64, follow: bool) -> Result<(), PlurkError> {
+        self.call_ok(
+            FRIENDS_FANS_SET_FOLLOWING_URL,
+            Some([
+                ("user_id", user_id.to_string()),
+                ("follow", follow.to_string()),
+            ]),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_completion() {
+        let raw = r#"{
+            "3": {"nick_name": "alvin", "full_name": "Alvin", "display_name": "amix"},
+            "5": {"nick_name": "bob"}
+        }"#;
+        let completion: HashMap<u64, Completion> = serde_json::from_str(raw).unwrap();
+        assert_eq!(completion[&3].display_name.as_deref(), Some("amix"));
+        assert_eq!(completion[&5].nick_name, "bob");
+        assert_eq!(completion[&5].full_name, None);
+    }
+
+    #[test]
+    fn test_deserialize_following() {
+        let raw = r#"[
+            {"id": 3, "nick_name": "alvin", "following": true},
+            {"id": 5, "nick_name": "bob", "following": false},
+            {"id": 7, "nick_name": "carol"}
+        ]"#;
+        let users: Vec<FollowingUser> = serde_json::from_str(raw).unwrap();
+        assert_eq!(users[0].user.nick_name, "alvin");
+        assert!(users[0].following);
+        assert!(!users[0].user.extra.contains_key("following"));
+        assert!(!users[1].following);
+        assert!(!users[2].following);
+    }
+
+    #[test]
+    fn test_next_users_offset() {
+        assert_eq!(next_users_offset(0, USERS_PAGE_LIMIT), Some(100));
+        assert_eq!(next_users_offset(100, USERS_PAGE_LIMIT), Some(200));
+        assert_eq!(next_users_offset(200, 3), None);
+        assert_eq!(next_users_offset(0, 0), None);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_get_friends_stream() {
+        use crate::testing::MockServer;
+
+        let server = MockServer::new().mock(
+            FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL,
+            r#"[{"id": 5, "nick_name": "amber"}, {"id": 7, "nick_name": "bob"}]"#,
+        );
+        let plurk = server.plurk();
+        let friends: Vec<PlurkUser> = plurk.get_friends_stream(3).try_collect().await.unwrap();
+        assert_eq!(friends.len(), 2);
+        assert_eq!(friends[1].nick_name, "bob");
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(server.requests()[0].param("user_id"), Some("3"));
+    }
+}
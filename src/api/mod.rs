@@ -0,0 +1,151 @@
+/// Declare thin endpoint wrappers on [`Plurk`](crate::plurk::Plurk).
+///
+/// Each entry names the path constant and the wrapper; parameters are sent as form
+/// fields named after the arguments using their `to_string()` value. Without a return
+/// type the endpoint is expected to answer `{"success_text": "ok"}`.
+///
+/// ```ignore
+/// plurk_endpoint! {
+///     const BLOCKS_BLOCK_URL = "/APP/Blocks/block";
+///     /// Block `user_id`.
+///     pub async fn block(user_id: u64);
+/// }
+/// ```
+macro_rules! plurk_endpoint {
+    (@ret) => { () };
+    (@ret $ret:ty) => { $ret };
+    (@call $plurk:ident, $url:ident, $query:ident) => {
+        $plurk.call_ok($url, Some($query)).await
+    };
+    (@call $plurk:ident, $url:ident, $query:ident, $ret:ty) => {
+        $plurk.call($url, Some($query)).await
+    };
+    ($(
+        const $url:ident = $path:literal;
+        $(#[$meta:meta])*
+        $vis:vis async fn $name:ident($($param:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;
+    )*) => {
+        $(pub(crate) const $url: &str = $path;)*
+
+        impl $crate::plurk::Plurk {
+            $(
+                $(#[$meta])*
+                $vis async fn $name(
+                    &self,
+                    $($param: $ty),*
+                ) -> Result<plurk_endpoint!(@ret $($ret)?), $crate::plurk::PlurkError> {
+                    let query: Vec<(&'static str, String)> =
+                        vec![$((stringify!($param), $param.to_string())),*];
+                    plurk_endpoint!(@call self, $url, query $(, $ret)?)
+                }
+            )*
+        }
+    };
+}
+
+pub mod alerts;
+pub mod blocks;
+pub mod cliques;
+pub mod coverage;
+pub mod emoticons;
+pub mod error;
+pub mod friends_fans;
+pub mod oauth;
+pub mod plurk_top;
+pub mod polling;
+pub mod profile;
+pub mod realtime;
+pub mod responses;
+pub mod search;
+pub mod timeline;
+pub mod users;
+
+use crate::plurk::PlurkError;
+
+/// Maximum length, in characters, of plurk and response content.
+pub const MAX_CONTENT_LENGTH: usize = 360;
+
+pub(crate) fn content_too_long() -> PlurkError {
+    PlurkError::InvalidArgument(format!("Content exceeds {} characters", MAX_CONTENT_LENGTH))
+}
+
+/// Reject content Plurk would refuse: empty, or longer than [`MAX_CONTENT_LENGTH`].
+pub(crate) fn check_content(content: &str) -> Result<(), PlurkError> {
+    if content.trim().is_empty() {
+        return Err(PlurkError::InvalidArgument(String::from(
+            "Content is empty",
+        )));
+    }
+    if content.chars().count() > MAX_CONTENT_LENGTH {
+        return Err(content_too_long());
+    }
+    Ok(())
+}
+
+/// Start of the URLs returned by [`Plurk::upload_picture`](crate::plurk::Plurk::upload_picture).
+pub(crate) const IMAGE_URL_PREFIX: &str = "https://images.plurk.com/";
+
+/// Append image URLs to the content; Plurk renders bare image URLs inline.
+pub(crate) fn append_image_urls(text: &str, urls: &[String]) -> String {
+    let text = text.trim_end();
+    let mut parts: Vec<&str> = Vec::with_capacity(urls.len() + 1);
+    if !text.is_empty() {
+        parts.push(text);
+    }
+    parts.extend(urls.iter().map(String::as_str));
+    parts.join(" ")
+}
+
+/// Fail before uploading `images` pictures when `text` leaves no room for their URLs,
+/// each taking at least [`IMAGE_URL_PREFIX`] plus one character and a separator.
+pub(crate) fn check_image_room(text: &str, images: usize) -> Result<(), PlurkError> {
+    let urls = vec![format!("{}x", IMAGE_URL_PREFIX); images];
+    if append_image_urls(text, &urls).chars().count() > MAX_CONTENT_LENGTH {
+        return Err(content_too_long());
+    }
+    Ok(())
+}
+
+/// Plurk expects lists of ids as a JSON array, e.g. `ids=[1,2,3]`.
+pub(crate) fn encode_ids(ids: &[u64]) -> String {
+    serde_json::to_string(ids).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_ids() {
+        assert_eq!(encode_ids(&[1, 22, 333]), "[1,22,333]");
+        assert_eq!(encode_ids(&[]), "[]");
+    }
+
+    #[test]
+    fn test_append_image_urls() {
+        let urls = [
+            String::from("https://images.plurk.com/a.jpg"),
+            String::from("https://images.plurk.com/b.jpg"),
+        ];
+        assert_eq!(
+            append_image_urls("look ", &urls[..1]),
+            "look https://images.plurk.com/a.jpg"
+        );
+        assert_eq!(
+            append_image_urls("", &urls),
+            "https://images.plurk.com/a.jpg https://images.plurk.com/b.jpg"
+        );
+        assert_eq!(append_image_urls("text", &[]), "text");
+    }
+
+    #[test]
+    fn test_check_image_room() {
+        // "text" + (" " + prefix + "x") per image must stay within the limit.
+        let url_len = IMAGE_URL_PREFIX.len() + 2;
+        let text = "a".repeat(MAX_CONTENT_LENGTH - 2 * url_len);
+        assert!(check_image_room(&text, 2).is_ok());
+        assert!(check_image_room(&text, 3).is_err());
+        assert!(check_image_room(&format!("{}a", text), 2).is_err());
+        assert!(check_image_room("", 0).is_ok());
+    }
+}
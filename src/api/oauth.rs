@@ -0,0 +1,97 @@
+use crate::models::time::{self, plurk_date_option};
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+pub(crate) const CHECK_TOKEN_URL: &str = "/APP/checkToken";
+pub(crate) const EXPIRE_TOKEN_URL: &str = "/APP/expireToken";
+pub(crate) const CHECK_TIME_URL: &str = "/APP/checkTime";
+pub(crate) const ECHO_URL: &str = "/APP/echo";
+
+/// The access token used for the request, see [`Plurk::check_token`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenInfo {
+    #[serde(default)]
+    pub user_id: u64,
+    #[serde(default)]
+    pub app_id: u64,
+    /// When the token was issued.
+    #[serde(default, with = "plurk_date_option")]
+    pub issued: Option<DateTime<Utc>>,
+}
+
+/// Server clock, see [`Plurk::check_time`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerTime {
+    /// Unix timestamp of the server.
+    pub timestamp: i64,
+    /// The same time as a date.
+    #[serde(default, with = "time::plurk_date")]
+    pub now: DateTime<Utc>,
+}
+
+impl ServerTime {
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(self.timestamp, 0).single()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Echo {
+    data: String,
+}
+
+impl Plurk {
+    /// Information about the access token; fails when it is no longer valid.
+    pub async fn check_token(&self) -> Result<TokenInfo, PlurkError> {
+        self.call(CHECK_TOKEN_URL, None::<()>).await
+    }
+
+    /// Invalidate the access token on Plurk. Later calls with it fail until the user
+    /// authorizes the application again.
+    pub async fn expire_token(&self) -> Result<TokenInfo, PlurkError> {
+        self.call(EXPIRE_TOKEN_URL, None::<()>).await
+    }
+
+    /// The server clock, useful to detect a local clock skewed enough to break OAuth
+    /// timestamps.
+    pub async fn check_time(&self) -> Result<ServerTime, PlurkError> {
+        self.call(CHECK_TIME_URL, None::<()>).await
+    }
+
+    /// Send `data` and return what the server echoes back.
+    pub async fn echo(&self, data: &str) -> Result<String, PlurkError> {
+        let echo: Echo = self
+            .call(ECHO_URL, Some([("data", data.to_string())]))
+            .await?;
+        Ok(echo.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_utilities() {
+        let raw = r#"{"user_id": 3, "app_id": 7, "issued": "Fri, 05 Jun 2009 23:07:13 GMT"}"#;
+        let token: TokenInfo = serde_json::from_str(raw).unwrap();
+        assert_eq!((token.user_id, token.app_id), (3, 7));
+        assert_eq!(
+            token.issued,
+            Some(Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, 13).unwrap())
+        );
+
+        let raw =
+            r#"{"app_id": 7, "timestamp": 1244243233, "now": "Fri, 05 Jun 2009 23:07:13 GMT"}"#;
+        let time: ServerTime = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            time.time(),
+            Some(Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, 13).unwrap())
+        );
+        assert_eq!(time.time(), Some(time.now));
+
+        let echo: Echo = serde_json::from_str(r#"{"length": 4, "data": "ping"}"#).unwrap();
+        assert_eq!(echo.data, "ping");
+    }
+}
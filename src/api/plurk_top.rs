@@ -0,0 +1,245 @@
+use crate::models::{PlurkLang, PlurkPost};
+use crate::plurk::{Plurk, PlurkError};
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+pub(crate) const PLURK_TOP_GET_COLLECTIONS_URL: &str = "/APP/PlurkTop/getCollections";
+pub(crate) const PLURK_TOP_GET_PLURKS_URL: &str = "/APP/PlurkTop/getPlurks";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlurkTopSorting {
+    #[default]
+    Hot,
+    New,
+}
+
+impl fmt::Display for PlurkTopSorting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Hot => write!(f, "hot"),
+            Self::New => write!(f, "new"),
+        }
+    }
+}
+
+/// A PlurkTop collection, see [`Plurk::get_plurk_top_collections`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlurkTopCollection {
+    Taiwan,
+    HongKong,
+    English,
+    Japanese,
+    /// A collection this crate does not know yet, by its name.
+    Other(String),
+}
+
+impl PlurkTopCollection {
+    /// Every collection known to this crate.
+    pub const ALL: [Self; 4] = [Self::Taiwan, Self::HongKong, Self::English, Self::Japanese];
+
+    /// The `collection_name` sent to Plurk.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Taiwan => "tw",
+            Self::HongKong => "hk",
+            Self::English => "en",
+            Self::Japanese => "ja",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for PlurkTopCollection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl From<&str> for PlurkTopCollection {
+    fn from(name: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|collection| collection.name() == name)
+            .unwrap_or_else(|| Self::Other(name.to_string()))
+    }
+}
+
+/// Unknown names become [`PlurkTopCollection::Other`].
+impl<'de> Deserialize<'de> for PlurkTopCollection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Ok(Self::from(raw.as_str()))
+    }
+}
+
+/// Filters for [`Plurk::plurk_top_stream`], all sent to Plurk.
+#[derive(Debug, Clone, Default)]
+pub struct PlurkTopFilter {
+    collection: Option<PlurkTopCollection>,
+    sorting: PlurkTopSorting,
+    topic_id: Option<u64>,
+    lang: Option<PlurkLang>,
+    limit: Option<usize>,
+}
+
+impl PlurkTopFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn collection(mut self, collection: PlurkTopCollection) -> Self {
+        self.collection = Some(collection);
+        self
+    }
+
+    pub fn sorting(mut self, sorting: PlurkTopSorting) -> Self {
+        self.sorting = sorting;
+        self
+    }
+
+    /// Only plurks of the PlurkTop topic with this id.
+    pub fn topic(mut self, topic_id: u64) -> Self {
+        self.topic_id = Some(topic_id);
+        self
+    }
+
+    pub fn lang(mut self, lang: PlurkLang) -> Self {
+        self.lang = Some(lang);
+        self
+    }
+
+    /// Plurks requested per page.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn to_query(&self, offset: Option<f64>) -> Vec<(&'static str, String)> {
+        let mut query = vec![("sorting", self.sorting.to_string())];
+        if let Some(collection) = &self.collection {
+            query.push(("collection_name", collection.to_string()));
+        }
+        if let Some(topic_id) = self.topic_id {
+            query.push(("topic_id", topic_id.to_string()));
+        }
+        if let Some(lang) = &self.lang {
+            query.push(("lang", lang.to_string()));
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = offset {
+            query.push(("offset", offset.to_string()));
+        }
+        query
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct PlurkTopPage {
+    #[serde(default)]
+    plurks: Vec<PlurkPost>,
+    #[serde(default)]
+    offset: Option<f64>,
+}
+
+impl PlurkTopPage {
+    fn next_offset(&self, offset: Option<f64>) -> Option<f64> {
+        if self.plurks.is_empty() || self.offset == offset {
+            None
+        } else {
+            self.offset
+        }
+    }
+}
+
+impl Plurk {
+    pub async fn get_plurk_top_collections(&self) -> Result<Vec<PlurkTopCollection>, PlurkError> {
+        self.call(PLURK_TOP_GET_COLLECTIONS_URL, None::<()>).await
+    }
+
+    /// Stream the PlurkTop plurks matching `filter`, following the returned offset token.
+    pub fn plurk_top_stream(
+        &self,
+        filter: PlurkTopFilter,
+    ) -> impl Stream<Item = Result<PlurkPost, PlurkError>> + '_ {
+        stream::try_unfold(Some(None), move |offset| {
+            let filter = filter.clone();
+            async move {
+                let Some(offset) = offset else {
+                    return Ok::<_, PlurkError>(None);
+                };
+                let page: PlurkTopPage = self
+                    .call(PLURK_TOP_GET_PLURKS_URL, Some(filter.to_query(offset)))
+                    .await?;
+                let next = page.next_offset(offset).map(Some);
+                let plurks = page.plurks.into_iter().map(Ok);
+                Ok(Some((stream::iter(plurks), next)))
+            }
+        })
+        .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_query() {
+        let filter = PlurkTopFilter::new()
+            .collection(PlurkTopCollection::Taiwan)
+            .sorting(PlurkTopSorting::New)
+            .topic(12)
+            .lang(PlurkLang::TraditionalChinese)
+            .limit(30);
+        assert_eq!(
+            filter.to_query(Some(0.5)),
+            [
+                ("sorting", String::from("new")),
+                ("collection_name", String::from("tw")),
+                ("topic_id", String::from("12")),
+                ("lang", String::from("tr_ch")),
+                ("limit", String::from("30")),
+                ("offset", String::from("0.5")),
+            ]
+        );
+        assert_eq!(
+            PlurkTopFilter::new().to_query(None),
+            [("sorting", String::from("hot"))]
+        );
+    }
+
+    #[test]
+    fn test_collection() {
+        let collections: Vec<PlurkTopCollection> =
+            serde_json::from_str(r#"["tw", "en", "kr"]"#).unwrap();
+        assert_eq!(
+            collections,
+            [
+                PlurkTopCollection::Taiwan,
+                PlurkTopCollection::English,
+                PlurkTopCollection::Other(String::from("kr")),
+            ]
+        );
+        for collection in PlurkTopCollection::ALL {
+            assert_eq!(PlurkTopCollection::from(collection.name()), collection);
+        }
+    }
+
+    #[test]
+    fn test_next_offset() {
+        let raw = r#"{"plurks": [{"plurk_id": 1, "owner_id": 1, "content": "",
+            "qualifier": ":", "posted": "Fri, 05 Jun 2009 23:07:13 GMT"}], "offset": 0.25}"#;
+        let page: PlurkTopPage = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.next_offset(None), Some(0.25));
+        assert_eq!(page.next_offset(Some(0.25)), None);
+
+        let page: PlurkTopPage = serde_json::from_str(r#"{"plurks": []}"#).unwrap();
+        assert_eq!(page.next_offset(Some(0.25)), None);
+    }
+}
@@ -0,0 +1,367 @@
+use crate::models::{id_map, time, PlurkPost, PlurkUser};
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+pub(crate) const POLLING_GET_PLURKS_URL: &str = "/APP/Polling/getPlurks";
+pub(crate) const POLLING_GET_UNREAD_COUNT_URL: &str = "/APP/Polling/getUnreadCount";
+const POLL_LIMIT: usize = 20;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnreadCount {
+    #[serde(default)]
+    pub all: u64,
+    #[serde(default)]
+    pub my: u64,
+    #[serde(default)]
+    pub private: u64,
+    #[serde(default)]
+    pub responded: u64,
+}
+
+/// Where [`Plurk::poll_unread`] left off. Create the first one from a time, e.g.
+/// `PollWatermark::from(Utc::now())`, then pass [`UnreadPoll::watermark`] to the next poll.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PollWatermark {
+    /// Posting time of the newest plurk returned so far.
+    pub posted: DateTime<Utc>,
+    /// Plurks posted in the same second as `posted` that were already returned. Times
+    /// only have second precision, so the next poll includes that second again and
+    /// skips these.
+    pub seen: Vec<u64>,
+}
+
+impl From<DateTime<Utc>> for PollWatermark {
+    fn from(posted: DateTime<Utc>) -> Self {
+        Self {
+            posted,
+            seen: Vec::new(),
+        }
+    }
+}
+
+/// Result of [`Plurk::poll_unread`].
+#[derive(Debug, Clone)]
+pub struct UnreadPoll {
+    /// Unread counters at the time of the poll. They leave out plurks already read in
+    /// another client and the user's own plurks, so `plurks` may be non-empty even when
+    /// they are zero.
+    pub unread: UnreadCount,
+    /// Plurks posted after the given watermark, oldest first.
+    pub plurks: Vec<PlurkPost>,
+    /// Pass this to the next `poll_unread` call.
+    pub watermark: PollWatermark,
+}
+
+/// Result of [`Plurk::poll_plurks`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PollingPage {
+    #[serde(default)]
+    pub plurks: Vec<PlurkPost>,
+    /// Owners of `plurks`, by user id.
+    #[serde(default, deserialize_with = "id_map::deserialize")]
+    pub plurk_users: HashMap<u64, PlurkUser>,
+}
+
+/// Keep the plurks posted at or after `since` that are not in `seen` yet.
+fn select_new(
+    plurks: Vec<PlurkPost>,
+    since: &DateTime<Utc>,
+    seen: &mut HashSet<u64>,
+) -> Vec<PlurkPost> {
+    plurks
+        .into_iter()
+        .filter(|p| p.posted >= *since)
+        .filter(|p| seen.insert(p.plurk_id))
+        .collect()
+}
+
+/// The watermark after `plurks` were returned on top of `watermark`.
+fn advance_watermark(watermark: PollWatermark, plurks: &[PlurkPost]) -> PollWatermark {
+    let Some(posted) = plurks.iter().map(|p| p.posted).max() else {
+        return watermark;
+    };
+    let mut seen = if posted == watermark.posted {
+        watermark.seen
+    } else {
+        Vec::new()
+    };
+    seen.extend(
+        plurks
+            .iter()
+            .filter(|p| p.posted == posted)
+            .map(|p| p.plurk_id),
+    );
+    PollWatermark { posted, seen }
+}
+
+impl Plurk {
+    /// Unread counters of the timeline, cheaper to call than fetching plurks.
+    pub async fn get_unread_count(&self) -> Result<UnreadCount, PlurkError> {
+        self.call(POLLING_GET_UNREAD_COUNT_URL, None::<()>).await
+    }
+
+    /// Plurks posted after `since`, at most `limit` of them. Plurk recommends this over
+    /// the timeline endpoints for periodic syncing, see also [`Self::poll_unread`].
+    pub async fn poll_plurks(
+        &self,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<PollingPage, PlurkError> {
+        self.call(
+            POLLING_GET_PLURKS_URL,
+            Some([
+                ("offset", time::format_offset(&since)),
+                ("limit", limit.to_string()),
+            ]),
+        )
+        .await
+    }
+
+    /// Fetch the plurks posted after `watermark`, together with the unread counters.
+    ///
+    /// The plurks are always paged from `watermark`, since the counters do not cover
+    /// every new plurk.
+    pub async fn poll_unread(&self, watermark: PollWatermark) -> Result<UnreadPoll, PlurkError> {
+        let unread = self.get_unread_count().await?;
+        let mut plurks = Vec::new();
+        let mut seen: HashSet<u64> = watermark.seen.iter().copied().collect();
+        // The offset is exclusive and only has second precision; start one second early
+        // so plurks sharing the watermark's second are not lost.
+        let mut offset = watermark.posted - Duration::seconds(1);
+        loop {
+            let page = self.poll_plurks(offset, POLL_LIMIT).await?;
+            let page_len = page.plurks.len();
+            let Some(newest) = page.plurks.iter().map(|p| p.posted).max() else {
+                break;
+            };
+            let fresh = select_new(page.plurks, &watermark.posted, &mut seen);
+            if page_len < POLL_LIMIT {
+                plurks.extend(fresh);
+                break;
+            }
+            // Step back one second so plurks sharing the newest second are picked up by
+            // the next page. When a full page brought nothing new, step past that second
+            // instead, otherwise the same page would be fetched forever.
+            offset = if fresh.is_empty() {
+                if newest <= offset {
+                    break;
+                }
+                newest
+            } else {
+                newest - Duration::seconds(1)
+            };
+            plurks.extend(fresh);
+        }
+
+        plurks.sort_by_key(|p| p.posted);
+        let watermark = advance_watermark(watermark, &plurks);
+
+        Ok(UnreadPoll {
+            unread,
+            plurks,
+            watermark,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn post(plurk_id: u64, posted: &str) -> PlurkPost {
+        PlurkPost {
+            plurk_id,
+            posted: time::parse_posted(posted).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select_new() {
+        let watermark = Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, 13).unwrap();
+        let mut seen = HashSet::from([2]);
+        let res = select_new(
+            vec![
+                post(1, "Fri, 05 Jun 2009 23:07:12 GMT"),
+                post(2, "Fri, 05 Jun 2009 23:07:13 GMT"),
+                post(3, "Fri, 05 Jun 2009 23:07:14 GMT"),
+            ],
+            &watermark,
+            &mut seen,
+        );
+        assert_eq!(res.iter().map(|p| p.plurk_id).collect::<Vec<_>>(), [3]);
+
+        let res = select_new(
+            vec![
+                post(3, "Fri, 05 Jun 2009 23:07:14 GMT"),
+                post(4, "Fri, 05 Jun 2009 23:07:13 GMT"),
+                post(5, "Fri, 05 Jun 2009 23:07:15 GMT"),
+            ],
+            &watermark,
+            &mut seen,
+        );
+        assert_eq!(res.iter().map(|p| p.plurk_id).collect::<Vec<_>>(), [4, 5]);
+    }
+
+    #[test]
+    fn test_advance_watermark() {
+        let at = |s| Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, s).unwrap();
+        let watermark = PollWatermark {
+            posted: at(13),
+            seen: vec![2],
+        };
+        assert_eq!(advance_watermark(watermark.clone(), &[]), watermark);
+
+        let plurks = [post(4, "Fri, 05 Jun 2009 23:07:13 GMT")];
+        let next = advance_watermark(watermark.clone(), &plurks);
+        assert_eq!(next.posted, at(13));
+        assert_eq!(next.seen, [2, 4]);
+
+        let plurks = [
+            post(4, "Fri, 05 Jun 2009 23:07:13 GMT"),
+            post(5, "Fri, 05 Jun 2009 23:07:15 GMT"),
+            post(6, "Fri, 05 Jun 2009 23:07:15 GMT"),
+        ];
+        let next = advance_watermark(watermark, &plurks);
+        assert_eq!(next.posted, at(15));
+        assert_eq!(next.seen, [5, 6]);
+    }
+
+    #[cfg(feature = "testing")]
+    fn polling_page(plurks: &[(u64, &str)]) -> String {
+        let plurks: Vec<String> = plurks
+            .iter()
+            .map(|(id, posted)| {
+                format!(
+                    r#"{{"plurk_id": {}, "owner_id": 3, "content": "", "qualifier": ":", "posted": "{}"}}"#,
+                    id, posted
+                )
+            })
+            .collect();
+        format!(r#"{{"plurks": [{}]}}"#, plurks.join(","))
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_poll_unread_same_second() {
+        use crate::testing::MockServer;
+
+        let unread = r#"{"all": 1}"#;
+        let server = MockServer::new()
+            .mock(POLLING_GET_UNREAD_COUNT_URL, unread)
+            .mock(
+                POLLING_GET_PLURKS_URL,
+                polling_page(&[(1, "Fri, 05 Jun 2009 23:07:13 GMT")]),
+            );
+        let watermark = Utc.with_ymd_and_hms(2009, 6, 5, 0, 0, 0).unwrap();
+        let poll = server.plurk().poll_unread(watermark.into()).await.unwrap();
+        assert_eq!(poll.plurks[0].plurk_id, 1);
+
+        // Plurk 2 shares the second of plurk 1 but only shows up on the next poll.
+        let server = MockServer::new()
+            .mock(POLLING_GET_UNREAD_COUNT_URL, unread)
+            .mock(
+                POLLING_GET_PLURKS_URL,
+                polling_page(&[
+                    (1, "Fri, 05 Jun 2009 23:07:13 GMT"),
+                    (2, "Fri, 05 Jun 2009 23:07:13 GMT"),
+                ]),
+            );
+        let poll = server.plurk().poll_unread(poll.watermark).await.unwrap();
+        assert_eq!(
+            poll.plurks.iter().map(|p| p.plurk_id).collect::<Vec<_>>(),
+            [2]
+        );
+        assert_eq!(
+            server.requests()[1].param("offset"),
+            Some("2009-6-5T23:07:12")
+        );
+        assert_eq!(poll.watermark.seen, [1, 2]);
+
+        let poll = server.plurk().poll_unread(poll.watermark).await.unwrap();
+        assert!(poll.plurks.is_empty());
+        assert_eq!(poll.watermark.seen, [1, 2]);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_poll_unread_full_page_same_second() {
+        use crate::testing::MockServer;
+
+        let plurks: Vec<(u64, &str)> = (1..=POLL_LIMIT as u64)
+            .map(|id| (id, "Fri, 05 Jun 2009 23:07:13 GMT"))
+            .collect();
+        let server = MockServer::new()
+            .mock(POLLING_GET_UNREAD_COUNT_URL, r#"{"all": 0}"#)
+            .mock(POLLING_GET_PLURKS_URL, polling_page(&plurks));
+        let watermark = Utc.with_ymd_and_hms(2009, 6, 5, 0, 0, 0).unwrap();
+        let poll = server.plurk().poll_unread(watermark.into()).await.unwrap();
+        assert_eq!(poll.plurks.len(), POLL_LIMIT);
+
+        // The page repeats itself once, then the poll steps past its second.
+        let offsets: Vec<String> = server
+            .requests()
+            .iter()
+            .filter_map(|r| r.param("offset").map(String::from))
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                "2009-6-4T23:59:59",
+                "2009-6-5T23:07:12",
+                "2009-6-5T23:07:13"
+            ]
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_poll_unread_without_unread_count() {
+        use crate::testing::{fixtures, MockServer};
+
+        let server = MockServer::new()
+            .mock(
+                POLLING_GET_UNREAD_COUNT_URL,
+                r#"{"all": 0, "my": 0, "private": 0, "responded": 0}"#,
+            )
+            .mock(POLLING_GET_PLURKS_URL, fixtures::GET_PLURKS);
+        let watermark = Utc.with_ymd_and_hms(2009, 6, 5, 0, 0, 0).unwrap();
+        let poll = server.plurk().poll_unread(watermark.into()).await.unwrap();
+        assert_eq!(poll.unread.all, 0);
+        assert_eq!(
+            poll.plurks.iter().map(|p| p.plurk_id).collect::<Vec<_>>(),
+            [1_495_754_903, 1_495_754_904]
+        );
+        assert_eq!(poll.watermark.posted, poll.plurks[1].posted);
+        assert_eq!(poll.watermark.seen, [1_495_754_904]);
+    }
+
+    #[test]
+    fn test_deserialize_polling_page() {
+        let raw = r#"{
+            "plurks": [{"plurk_id": 1, "owner_id": 3, "content": "", "qualifier": ":", "posted": "Fri, 05 Jun 2009 23:07:13 GMT"}],
+            "plurk_users": {"3": {"id": 3, "nick_name": "alvin"}}
+        }"#;
+        let page: PollingPage = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.plurks[0].plurk_id, 1);
+        assert_eq!(page.plurk_users[&3].nick_name, "alvin");
+    }
+
+    #[test]
+    fn test_deserialize_unread_count() {
+        let raw = r#"{"all": 2, "my": 1, "private": 1, "responded": 0}"#;
+        let count: UnreadCount = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            count,
+            UnreadCount {
+                all: 2,
+                my: 1,
+                private: 1,
+                responded: 0
+            }
+        );
+    }
+}
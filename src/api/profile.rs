@@ -0,0 +1,205 @@
+use crate::models::{id_map, PlurkPost, PlurkUser};
+use crate::plurk::{Plurk, PlurkError};
+use serde::{Deserialize, Deserializer};
+use std::{collections::HashMap, fmt};
+
+pub(crate) const PROFILE_GET_OWN_PROFILE_URL: &str = "/APP/Profile/getOwnProfile";
+pub(crate) const PROFILE_GET_PUBLIC_PROFILE_URL: &str = "/APP/Profile/getPublicProfile";
+
+/// The authorized user's profile page, see [`Plurk::get_own_profile`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct OwnProfile {
+    pub user_info: PlurkUser,
+    #[serde(default)]
+    pub friends_count: u64,
+    #[serde(default)]
+    pub fans_count: u64,
+    /// Unread plurks on the timeline.
+    #[serde(default)]
+    pub unread_count: u64,
+    #[serde(default)]
+    pub alerts_count: u64,
+    /// Recent plurks of the user.
+    #[serde(default)]
+    pub plurks: Vec<PlurkPost>,
+    /// Users appearing in `plurks`, by user id.
+    #[serde(default, deserialize_with = "id_map::deserialize")]
+    pub plurks_users: HashMap<u64, PlurkUser>,
+}
+
+/// A user given by id or by nick name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserRef {
+    Id(u64),
+    NickName(String),
+}
+
+impl From<u64> for UserRef {
+    fn from(id: u64) -> Self {
+        Self::Id(id)
+    }
+}
+
+impl From<&str> for UserRef {
+    fn from(nick_name: &str) -> Self {
+        Self::NickName(nick_name.to_string())
+    }
+}
+
+impl From<String> for UserRef {
+    fn from(nick_name: String) -> Self {
+        Self::NickName(nick_name)
+    }
+}
+
+impl fmt::Display for UserRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "{}", id),
+            Self::NickName(nick_name) => write!(f, "{}", nick_name),
+        }
+    }
+}
+
+/// Another user's profile page, see [`Plurk::get_public_profile`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PublicProfile {
+    pub user_info: PlurkUser,
+    #[serde(default)]
+    pub friends_count: u64,
+    #[serde(default)]
+    pub fans_count: u64,
+    /// Whether the authorized user may read the plurks, which are empty otherwise.
+    #[serde(default)]
+    pub has_read_permission: bool,
+    #[serde(default)]
+    pub plurks: Vec<PlurkPost>,
+    #[serde(flatten)]
+    pub relationship: Relationship,
+}
+
+/// How the authorized user relates to another user.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Relationship {
+    /// Both users are friends.
+    #[serde(default, rename = "are_friends", deserialize_with = "null_as_false")]
+    pub friend: bool,
+    /// The authorized user is a fan of the other user.
+    #[serde(default, rename = "is_fan", deserialize_with = "null_as_false")]
+    pub fan: bool,
+    /// The other user's plurks show up on the authorized user's timeline.
+    #[serde(default, rename = "is_following", deserialize_with = "null_as_false")]
+    pub following: bool,
+}
+
+fn null_as_false<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<bool>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl Plurk {
+    pub async fn get_own_profile(&self) -> Result<OwnProfile, PlurkError> {
+        self.call(PROFILE_GET_OWN_PROFILE_URL, None::<()>).await
+    }
+
+    /// The profile of `user`, an id or a nick name.
+    pub async fn get_public_profile<U>(&self, user: U) -> Result<PublicProfile, PlurkError>
+    where
+        U: Into<UserRef>,
+    {
+        self.call(
+            PROFILE_GET_PUBLIC_PROFILE_URL,
+            Some([("user_id", user.into().to_string())]),
+        )
+        .await
+    }
+
+    /// Friend, fan and following status towards `user_id` in a single call.
+    pub async fn relationship_with(&self, user_id: u64) -> Result<Relationship, PlurkError> {
+        self.call(
+            PROFILE_GET_PUBLIC_PROFILE_URL,
+            Some([("user_id", user_id.to_string())]),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_own_profile() {
+        let raw = r#"{
+            "user_info": {"id": 3, "nick_name": "alvin"},
+            "privacy": "world",
+            "fans_count": 1,
+            "friends_count": 2,
+            "unread_count": 5,
+            "alerts_count": 0,
+            "plurks_users": {"3": {"id": 3, "nick_name": "alvin"}},
+            "plurks": [{
+                "plurk_id": 7, "owner_id": 3, "content": "hi", "qualifier": ":",
+                "posted": "Fri, 05 Jun 2009 23:07:13 GMT"
+            }]
+        }"#;
+        let profile: OwnProfile = serde_json::from_str(raw).unwrap();
+        assert_eq!(profile.user_info.id, 3);
+        assert_eq!((profile.friends_count, profile.fans_count), (2, 1));
+        assert_eq!(profile.unread_count, 5);
+        assert_eq!(profile.plurks[0].plurk_id, 7);
+        assert_eq!(profile.plurks_users[&3].nick_name, "alvin");
+    }
+
+    #[test]
+    fn test_deserialize_public_profile() {
+        let raw = r#"{
+            "user_info": {"id": 3, "nick_name": "alvin"},
+            "privacy": "world",
+            "fans_count": 1,
+            "friends_count": 2,
+            "has_read_permission": true,
+            "are_friends": true,
+            "is_fan": null,
+            "plurks": []
+        }"#;
+        let profile: PublicProfile = serde_json::from_str(raw).unwrap();
+        assert_eq!(profile.user_info.nick_name, "alvin");
+        assert!(profile.has_read_permission);
+        assert!(profile.relationship.friend);
+        assert!(!profile.relationship.fan);
+
+        assert_eq!(UserRef::from(3).to_string(), "3");
+        assert_eq!(UserRef::from("alvin").to_string(), "alvin");
+    }
+
+    #[test]
+    fn test_deserialize_relationship() {
+        let raw = r#"{
+            "user_info": {"id": 3, "nick_name": "alvin"},
+            "privacy": "world",
+            "fans_count": 1,
+            "friends_count": 2,
+            "are_friends": true,
+            "is_fan": false,
+            "is_following": true
+        }"#;
+        let rel: Relationship = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            rel,
+            Relationship {
+                friend: true,
+                fan: false,
+                following: true
+            }
+        );
+
+        // Anonymous requests get no relationship flags (or nulls) back.
+        let rel: Relationship = serde_json::from_str(r#"{"privacy": "world"}"#).unwrap();
+        assert_eq!(rel, Relationship::default());
+        let rel: Relationship = serde_json::from_str(r#"{"are_friends": null}"#).unwrap();
+        assert_eq!(rel, Relationship::default());
+    }
+}
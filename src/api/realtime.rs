@@ -0,0 +1,276 @@
+use crate::plurk::{Plurk, PlurkError, RawResponse, RequestOptions};
+use crate::runtime::PlurkRuntime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+
+pub(crate) const REALTIME_GET_USER_CHANNEL_URL: &str = "/APP/Realtime/getUserChannel";
+
+/// The comet server holds a poll open for up to about a minute before answering.
+const COMET_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// The comet channel pushing the user's timeline events, see [`Plurk::get_user_channel`].
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UserChannel {
+    /// Long-poll URL, already carrying the channel name.
+    pub comet_server: String,
+    pub channel_name: String,
+}
+
+/// One answer of the comet server, see [`Plurk::poll_channel`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CometResponse {
+    /// Offset for the next poll. Negative values are errors: `-1` when the poll timed out
+    /// without events, `-3` when the offset was no longer valid and a new channel should
+    /// be requested.
+    pub new_offset: i64,
+    /// Raw events, typically `new_plurk` and `new_response` objects.
+    #[serde(default)]
+    pub data: Vec<Value>,
+}
+
+/// Position in the comet stream saved by [`Plurk::follow_channel`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CometCheckpoint {
+    pub channel_name: String,
+    pub offset: i64,
+}
+
+fn io_error<E: ToString>(e: E) -> PlurkError {
+    PlurkError::IOError(e.to_string())
+}
+
+impl CometCheckpoint {
+    /// Read the checkpoint at `path`, `None` when the file does not exist.
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Option<Self>, PlurkError> {
+        match fs::read(path).await {
+            Ok(raw) => serde_json::from_slice(&raw).map(Some).map_err(io_error),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    /// Write the checkpoint through a temporary file, so a crash never leaves it half
+    /// written.
+    pub async fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), PlurkError> {
+        let path = path.as_ref();
+        let raw = serde_json::to_vec(self).map_err(io_error)?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, raw).await.map_err(io_error)?;
+        fs::rename(&tmp, path).await.map_err(io_error)
+    }
+}
+
+/// Extract the JSON of a `CometChannel.scriptCallback({...});` answer.
+fn parse_comet(body: &str) -> Result<CometResponse, PlurkError> {
+    let invalid = || PlurkError::APICallError(format!("Invalid comet response: {}", body));
+    let start = body.find('(').ok_or_else(invalid)?;
+    let end = body.rfind(')').ok_or_else(invalid)?;
+    if end <= start {
+        return Err(invalid());
+    }
+    serde_json::from_str(&body[start + 1..end]).map_err(|_| invalid())
+}
+
+impl Plurk {
+    pub async fn get_user_channel(&self) -> Result<UserChannel, PlurkError> {
+        self.call(REALTIME_GET_USER_CHANNEL_URL, None::<()>).await
+    }
+
+    /// Wait on the comet server for events after `offset`, `0` on the first poll and the
+    /// previous `new_offset` afterwards. The request is a GET to the comet host and
+    /// returns once events arrive or the server times out.
+    pub async fn poll_channel(
+        &self,
+        channel: &UserChannel,
+        offset: i64,
+    ) -> Result<CometResponse, PlurkError> {
+        let options = RequestOptions {
+            timeout: Some(COMET_TIMEOUT),
+        };
+        let res = self
+            .get_with(
+                channel.comet_server.as_str(),
+                Some([("offset", offset)]),
+                &options,
+            )
+            .await?;
+        let raw = RawResponse::read(res).await?;
+        parse_comet(&String::from_utf8_lossy(&raw.body))
+    }
+
+    /// Poll the user channel until `token` is cancelled, handing every batch of events
+    /// to `on_events`.
+    ///
+    /// Polling resumes from the offset saved at `checkpoint` when it belongs to the
+    /// current channel. A pending poll is abandoned on cancellation, and the offset after
+    /// the last handled batch is written back to `checkpoint` whenever the loop ends,
+    /// including on errors. A negative offset other than `-1` and `-3` ends the loop
+    /// with [`PlurkError::APICallError`] instead of polling again right away.
+    pub async fn follow_channel<P, E>(
+        &self,
+        checkpoint: P,
+        token: CancellationToken,
+        mut on_events: E,
+    ) -> Result<(), PlurkError>
+    where
+        P: AsRef<Path>,
+        E: FnMut(Vec<Value>),
+    {
+        let checkpoint = checkpoint.as_ref();
+        let mut channel = self.get_user_channel().await?;
+        let mut offset = match CometCheckpoint::load(checkpoint).await? {
+            Some(saved) if saved.channel_name == channel.channel_name => saved.offset,
+            _ => 0,
+        };
+        let res = loop {
+            let res = tokio::select! {
+                _ = token.cancelled() => break Ok(()),
+                res = self.poll_channel(&channel, offset) => res,
+            };
+            match res {
+                Ok(res) if res.new_offset >= 0 => {
+                    offset = res.new_offset;
+                    if !res.data.is_empty() {
+                        on_events(res.data);
+                    }
+                }
+                Ok(res) if res.new_offset == -3 => match self.get_user_channel().await {
+                    Ok(new) => {
+                        channel = new;
+                        offset = 0;
+                    }
+                    Err(e) => break Err(e),
+                },
+                Ok(res) if res.new_offset == -1 => {}
+                Ok(res) => {
+                    break Err(PlurkError::APICallError(format!(
+                        "Comet error offset: {}",
+                        res.new_offset
+                    )))
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        let saved = CometCheckpoint {
+            channel_name: channel.channel_name,
+            offset,
+        }
+        .save(checkpoint)
+        .await;
+        res.and(saved)
+    }
+}
+
+impl PlurkRuntime {
+    /// Follow the user channel with [`Plurk::follow_channel`] until the runtime shuts
+    /// down.
+    pub fn spawn_comet<E>(&mut self, plurk: Plurk, checkpoint: PathBuf, on_events: E)
+    where
+        E: FnMut(Vec<Value>) + Send + 'static,
+    {
+        self.spawn(
+            move |token| async move { plurk.follow_channel(checkpoint, token, on_events).await },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_comet() {
+        let body = r#"CometChannel.scriptCallback({"new_offset": 3, "data": [{"type": "new_plurk", "plurk_id": 5}]});"#;
+        let res = parse_comet(body).unwrap();
+        assert_eq!(res.new_offset, 3);
+        assert_eq!(res.data[0]["type"], "new_plurk");
+
+        let res = parse_comet(r#"CometChannel.scriptCallback({"new_offset": -1});"#).unwrap();
+        assert_eq!(res.new_offset, -1);
+        assert!(res.data.is_empty());
+
+        assert!(parse_comet("Bad gateway").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_user_channel() {
+        let raw = r#"{
+            "comet_server": "https://comet03.plurk.com/comet/1235515351741/?channel=generic-4-f733d",
+            "channel_name": "generic-4-f733d"
+        }"#;
+        let channel: UserChannel = serde_json::from_str(raw).unwrap();
+        assert_eq!(channel.channel_name, "generic-4-f733d");
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_shutdown_comet() {
+        use crate::testing::MockServer;
+        use tempdir::TempDir;
+        use tokio::sync::mpsc;
+
+        let server = MockServer::new()
+            .mock(
+                REALTIME_GET_USER_CHANNEL_URL,
+                r#"{"comet_server": "https://comet.plurk.com/comet?channel=c1", "channel_name": "c1"}"#,
+            )
+            .mock(
+                "/comet",
+                r#"CometChannel.scriptCallback({"new_offset": 5, "data": [{"type": "new_plurk"}]});"#,
+            );
+        let dir = TempDir::new("comet").unwrap();
+        let checkpoint = dir.path().join("comet.json");
+        CometCheckpoint {
+            channel_name: String::from("c1"),
+            offset: 2,
+        }
+        .save(&checkpoint)
+        .await
+        .unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut runtime = PlurkRuntime::new();
+        runtime.spawn_comet(server.plurk(), checkpoint.clone(), move |events| {
+            let _ = tx.send(events);
+        });
+        assert_eq!(rx.recv().await.unwrap()[0]["type"], "new_plurk");
+        assert!(runtime.shutdown().await.is_empty());
+
+        assert_eq!(server.requests()[1].param("offset"), Some("2"));
+        let saved = CometCheckpoint::load(&checkpoint).await.unwrap().unwrap();
+        assert_eq!(saved.offset, 5);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_follow_channel_error_offset() {
+        use crate::testing::MockServer;
+        use tempdir::TempDir;
+
+        let server = MockServer::new()
+            .mock(
+                REALTIME_GET_USER_CHANNEL_URL,
+                r#"{"comet_server": "https://comet.plurk.com/comet?channel=c1", "channel_name": "c1"}"#,
+            )
+            .mock("/comet", r#"CometChannel.scriptCallback({"new_offset": -2});"#);
+        let dir = TempDir::new("comet").unwrap();
+        let checkpoint = dir.path().join("comet.json");
+
+        let res = server
+            .plurk()
+            .follow_channel(&checkpoint, CancellationToken::new(), |_| {})
+            .await;
+        assert!(matches!(res, Err(PlurkError::APICallError(_))));
+        assert_eq!(server.requests().len(), 2);
+        let saved = CometCheckpoint::load(&checkpoint).await.unwrap().unwrap();
+        assert_eq!(saved.offset, 0);
+    }
+}
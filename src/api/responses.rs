@@ -0,0 +1,299 @@
+use crate::api::{append_image_urls, check_content, check_image_room};
+use crate::models::{id_map, PlurkResponse, PlurkUser, Qualifier};
+use crate::plurk::{Plurk, PlurkError};
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path};
+
+pub(crate) const RESPONSES_GET_URL: &str = "/APP/Responses/get";
+pub(crate) const RESPONSES_RESPONSE_ADD_URL: &str = "/APP/Responses/responseAdd";
+
+plurk_endpoint! {
+    const RESPONSES_RESPONSE_DELETE_URL = "/APP/Responses/responseDelete";
+    /// Delete `response_id` from `plurk_id`; an `error_text` answer becomes
+    /// [`PlurkError::Api`](crate::plurk::PlurkError::Api).
+    pub async fn delete_response(response_id: u64, plurk_id: u64);
+}
+
+/// A page of `/APP/Responses/get`, see [`Plurk::get_responses`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ResponsesPage {
+    pub responses: Vec<PlurkResponse>,
+    /// Total number of responses on the plurk.
+    #[serde(default)]
+    pub response_count: u64,
+    /// How many responses the user has already seen.
+    #[serde(default)]
+    pub responses_seen: u64,
+    /// Authors of the responses, by user id.
+    #[serde(default, deserialize_with = "id_map::deserialize")]
+    pub friends: HashMap<u64, PlurkUser>,
+}
+
+impl ResponsesPage {
+    /// Offset of the next page, or `None` once every response has been fetched.
+    fn next_offset(&self, from_response: u64) -> Option<u64> {
+        let next = from_response + self.responses.len() as u64;
+        if self.responses.is_empty() || next >= self.response_count {
+            None
+        } else {
+            Some(next)
+        }
+    }
+}
+
+/// Builder for `/APP/Responses/responseAdd`, created by [`Plurk::response_add`].
+#[derive(Debug, Clone)]
+pub struct ResponseAddBuilder<'a> {
+    plurk: &'a Plurk,
+    plurk_id: u64,
+    content: Option<String>,
+    qualifier: Option<Qualifier>,
+    anonymous: bool,
+}
+
+impl<'a> ResponseAddBuilder<'a> {
+    fn new(plurk: &'a Plurk, plurk_id: u64) -> Self {
+        Self {
+            plurk,
+            plurk_id,
+            content: None,
+            qualifier: None,
+            anonymous: false,
+        }
+    }
+
+    pub fn content<S: Into<String>>(mut self, content: S) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    pub fn qualifier(mut self, qualifier: Qualifier) -> Self {
+        self.qualifier = Some(qualifier);
+        self
+    }
+
+    /// Hide the author of the response.
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
+
+    fn to_query(&self) -> Result<Vec<(&'static str, String)>, PlurkError> {
+        let content = self.content.as_deref().unwrap_or_default();
+        check_content(content)?;
+        let qualifier = self.qualifier.as_ref().map_or("", Qualifier::as_str);
+        if qualifier.is_empty() {
+            return Err(PlurkError::InvalidArgument(String::from(
+                "Qualifier is required",
+            )));
+        }
+
+        let mut query = vec![
+            ("plurk_id", self.plurk_id.to_string()),
+            ("content", content.to_string()),
+            ("qualifier", qualifier.to_string()),
+        ];
+        if self.anonymous {
+            query.push(("anonymous", String::from("1")));
+        }
+        Ok(query)
+    }
+
+    pub async fn send(self) -> Result<PlurkResponse, PlurkError> {
+        let query = self.to_query()?;
+        let content = self.content.as_deref().unwrap_or_default();
+        let post = self.plurk.call(RESPONSES_RESPONSE_ADD_URL, Some(query));
+        self.plurk.guarded(Some(self.plurk_id), content, post).await
+    }
+}
+
+impl Plurk {
+    pub fn response_add(&self, plurk_id: u64) -> ResponseAddBuilder<'_> {
+        ResponseAddBuilder::new(self, plurk_id)
+    }
+
+    /// Respond to `plurk_id` and return the created response.
+    pub async fn respond(
+        &self,
+        plurk_id: u64,
+        content: &str,
+        qualifier: Qualifier,
+    ) -> Result<PlurkResponse, PlurkError> {
+        self.response_add(plurk_id)
+            .content(content)
+            .qualifier(qualifier)
+            .send()
+            .await
+    }
+
+    /// Upload `image` and respond to `plurk_id` with `text` followed by the picture.
+    ///
+    /// Fails before uploading when `text` leaves no room for the image URL.
+    pub async fn respond_with_image<S, P>(
+        &self,
+        plurk_id: u64,
+        text: S,
+        qualifier: Qualifier,
+        image: P,
+    ) -> Result<PlurkResponse, PlurkError>
+    where
+        S: AsRef<str>,
+        P: AsRef<Path>,
+    {
+        let text = text.as_ref();
+        check_image_room(text, 1)?;
+
+        let picture = self.upload_picture(image.as_ref()).await?;
+        let content = append_image_urls(text, &[picture.full]);
+        self.respond(plurk_id, &content, qualifier).await
+    }
+
+    /// Responses of `plurk_id` starting at index `from_response`, see
+    /// [`Self::responses_stream`] to get all of them.
+    pub async fn get_responses(
+        &self,
+        plurk_id: u64,
+        from_response: u64,
+    ) -> Result<ResponsesPage, PlurkError> {
+        self.call(
+            RESPONSES_GET_URL,
+            Some([
+                ("plurk_id", plurk_id.to_string()),
+                ("from_response", from_response.to_string()),
+            ]),
+        )
+        .await
+    }
+
+    /// Stream every response of a plurk, following `from_response` page by page.
+    pub fn responses_stream(
+        &self,
+        plurk_id: u64,
+    ) -> impl Stream<Item = Result<PlurkResponse, PlurkError>> + '_ {
+        stream::try_unfold(Some(0), move |from_response| async move {
+            let Some(from_response) = from_response else {
+                return Ok::<_, PlurkError>(None);
+            };
+            let page = self.get_responses(plurk_id, from_response).await?;
+            let next = page.next_offset(from_response);
+            Ok(Some((
+                stream::iter(page.responses.into_iter().map(Ok)),
+                next,
+            )))
+        })
+        .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{IMAGE_URL_PREFIX, MAX_CONTENT_LENGTH};
+
+    #[test]
+    fn test_response_add_query() {
+        let plurk = Plurk::new("c1", "c2", None, None);
+        assert!(plurk.response_add(7).content("hi").to_query().is_err());
+        assert!(plurk
+            .response_add(7)
+            .content(" ")
+            .qualifier(Qualifier::Says)
+            .to_query()
+            .is_err());
+        assert!(plurk
+            .response_add(7)
+            .content("a".repeat(MAX_CONTENT_LENGTH + 1))
+            .qualifier(Qualifier::Says)
+            .to_query()
+            .is_err());
+
+        let query = plurk
+            .response_add(7)
+            .content("hi")
+            .qualifier(Qualifier::Says)
+            .anonymous(true)
+            .to_query()
+            .unwrap();
+        assert_eq!(
+            query,
+            [
+                ("plurk_id", String::from("7")),
+                ("content", String::from("hi")),
+                ("qualifier", String::from("says")),
+                ("anonymous", String::from("1")),
+            ]
+        );
+
+        let query = plurk
+            .response_add(7)
+            .content("hi")
+            .qualifier(Qualifier::Freestyle)
+            .to_query()
+            .unwrap();
+        assert!(!query.iter().any(|(key, _)| *key == "anonymous"));
+    }
+
+    #[tokio::test]
+    async fn test_respond_with_image_too_long() {
+        let plurk = Plurk::new("c1", "c2", Some("t1"), Some("t2"));
+        let text = "a".repeat(MAX_CONTENT_LENGTH - IMAGE_URL_PREFIX.len());
+        let res = plurk
+            .respond_with_image(7, &text, Qualifier::Shares, "missing.jpg")
+            .await;
+        assert!(matches!(res, Err(PlurkError::InvalidArgument(_))));
+
+        // Short enough, so the upload is attempted and fails on the missing file.
+        let res = plurk
+            .respond_with_image(7, "look", Qualifier::Shares, "missing.jpg")
+            .await;
+        assert!(matches!(res, Err(PlurkError::APICallError(_))));
+    }
+
+    fn page(len: u64, response_count: u64) -> ResponsesPage {
+        let responses = (0..len)
+            .map(|id| PlurkResponse {
+                id,
+                ..Default::default()
+            })
+            .collect();
+        ResponsesPage {
+            responses,
+            response_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_next_offset() {
+        assert_eq!(page(20, 50).next_offset(0), Some(20));
+        assert_eq!(page(20, 50).next_offset(20), Some(40));
+        assert_eq!(page(10, 50).next_offset(40), None);
+        assert_eq!(page(0, 50).next_offset(40), None);
+        assert_eq!(page(0, 0).next_offset(0), None);
+    }
+
+    #[test]
+    fn test_deserialize_page() {
+        let raw = r#"{
+            "friends": {"3": {"id": 3, "nick_name": "alvin"}},
+            "responses_seen": 0,
+            "response_count": 1,
+            "responses": [{
+                "id": 9,
+                "user_id": 3,
+                "plurk_id": 5,
+                "content": "hello",
+                "content_raw": "hello",
+                "qualifier": ":",
+                "posted": "Fri, 05 Jun 2009 23:07:13 GMT",
+                "lang": "en"
+            }]
+        }"#;
+        let page: ResponsesPage = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.response_count, 1);
+        assert_eq!(page.responses[0].id, 9);
+        assert_eq!(page.friends[&3].nick_name, "alvin");
+        assert_eq!(page.next_offset(0), None);
+    }
+}
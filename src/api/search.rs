@@ -0,0 +1,161 @@
+use crate::models::{id_map, PlurkPost, PlurkUser};
+use crate::plurk::{Plurk, PlurkError};
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+pub(crate) const PLURK_SEARCH_URL: &str = "/APP/PlurkSearch/search";
+pub(crate) const USER_SEARCH_URL: &str = "/APP/UserSearch/search";
+
+/// A page of plurk search results, see [`Plurk::search_plurks`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SearchPage {
+    #[serde(default)]
+    pub plurks: Vec<PlurkPost>,
+    /// Owners of `plurks`, by user id.
+    #[serde(default, deserialize_with = "id_map::deserialize")]
+    pub users: HashMap<u64, PlurkUser>,
+    #[serde(default)]
+    pub has_more: bool,
+    /// Pass this as `offset` to continue the search.
+    #[serde(default)]
+    pub last_offset: Option<u64>,
+}
+
+impl SearchPage {
+    fn next_offset(&self, offset: Option<u64>) -> Option<u64> {
+        if !self.has_more || self.plurks.is_empty() || self.last_offset == offset {
+            None
+        } else {
+            self.last_offset
+        }
+    }
+}
+
+/// A page of user search results, see [`Plurk::search_users`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct UserSearchPage {
+    /// Number of matching users over all pages.
+    #[serde(default)]
+    pub counts: u64,
+    #[serde(default)]
+    pub users: Vec<PlurkUser>,
+}
+
+struct SearchState {
+    offset: Option<u64>,
+    seen: HashSet<u64>,
+}
+
+impl Plurk {
+    /// One page of plurks matching `query`, continuing from the `last_offset` of the
+    /// previous page when given.
+    pub async fn search_plurks(
+        &self,
+        query: &str,
+        offset: Option<u64>,
+    ) -> Result<SearchPage, PlurkError> {
+        let mut params = vec![("query", query.to_string())];
+        if let Some(offset) = offset {
+            params.push(("offset", offset.to_string()));
+        }
+        self.call(PLURK_SEARCH_URL, Some(params)).await
+    }
+
+    /// Users matching `query`, starting at `offset`.
+    pub async fn search_users(
+        &self,
+        query: &str,
+        offset: usize,
+    ) -> Result<UserSearchPage, PlurkError> {
+        self.call(
+            USER_SEARCH_URL,
+            Some([("query", query.to_string()), ("offset", offset.to_string())]),
+        )
+        .await
+    }
+
+    /// Stream every plurk matching `query`, following `last_offset` and skipping plurks
+    /// that show up again on a later page.
+    pub fn search_stream<S>(
+        &self,
+        query: S,
+    ) -> impl Stream<Item = Result<PlurkPost, PlurkError>> + '_
+    where
+        S: Into<String>,
+    {
+        let query: String = query.into();
+        let init = Some(SearchState {
+            offset: None,
+            seen: HashSet::new(),
+        });
+        stream::try_unfold(init, move |state| {
+            let query = query.clone();
+            async move {
+                let Some(mut state) = state else {
+                    return Ok::<_, PlurkError>(None);
+                };
+                let page = self.search_plurks(&query, state.offset).await?;
+                let next = page.next_offset(state.offset);
+                let plurks: Vec<PlurkPost> = page
+                    .plurks
+                    .into_iter()
+                    .filter(|p| state.seen.insert(p.plurk_id))
+                    .collect();
+                let state = next.map(|offset| SearchState {
+                    offset: Some(offset),
+                    seen: state.seen,
+                });
+                Ok(Some((stream::iter(plurks.into_iter().map(Ok)), state)))
+            }
+        })
+        .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_user_search() {
+        let raw = r#"{"counts": 12, "users": [{"id": 3, "nick_name": "alvin"}]}"#;
+        let page: UserSearchPage = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.counts, 12);
+        assert_eq!(page.users[0].nick_name, "alvin");
+    }
+
+    #[test]
+    fn test_next_offset() {
+        let raw = r#"{
+            "plurks": [{"plurk_id": 1, "owner_id": 1, "content": "", "qualifier": ":", "posted": "Fri, 05 Jun 2009 23:07:13 GMT"}],
+            "users": {"1": {"id": 1, "nick_name": "alvin"}},
+            "has_more": true,
+            "last_offset": 1234
+        }"#;
+        let page: SearchPage = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.users[&1].nick_name, "alvin");
+        assert_eq!(page.next_offset(None), Some(1234));
+        assert_eq!(page.next_offset(Some(1234)), None);
+
+        let raw = r#"{"plurks": [], "users": {}, "has_more": false, "last_offset": 0}"#;
+        let page: SearchPage = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.next_offset(Some(1234)), None);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_search_stream() {
+        use crate::testing::{fixtures, MockServer};
+        use futures::StreamExt;
+
+        let server = MockServer::new().mock(PLURK_SEARCH_URL, fixtures::GET_PLURKS);
+        let plurk = server.plurk();
+        let plurks: Vec<PlurkPost> = plurk.search_stream("hello").try_collect().await.unwrap();
+        assert_eq!(plurks.len(), 2);
+        assert_eq!(server.requests()[0].param("query"), Some("hello"));
+
+        let first: Vec<_> = plurk.search_stream("hello").take(1).collect().await;
+        assert_eq!(first.len(), 1);
+    }
+}
@@ -0,0 +1,1015 @@
+use crate::api::{
+    append_image_urls, check_content, check_image_room, content_too_long, encode_ids,
+    MAX_CONTENT_LENGTH,
+};
+pub use crate::models::CommentPermission;
+use crate::models::{id_map, time, LimitedTo, PlurkId, PlurkLang, PlurkPost, PlurkUser, Qualifier};
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::multipart;
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+pub(crate) const TIMELINE_GET_PLURK_URL: &str = "/APP/Timeline/getPlurk";
+pub(crate) const TIMELINE_GET_PLURKS_URL: &str = "/APP/Timeline/getPlurks";
+pub(crate) const TIMELINE_PLURK_ADD_URL: &str = "/APP/Timeline/plurkAdd";
+pub(crate) const TIMELINE_PLURK_EDIT_URL: &str = "/APP/Timeline/plurkEdit";
+pub(crate) const TIMELINE_PLURK_DELETE_URL: &str = "/APP/Timeline/plurkDelete";
+pub(crate) const TIMELINE_MUTE_PLURKS_URL: &str = "/APP/Timeline/mutePlurks";
+pub(crate) const TIMELINE_UNMUTE_PLURKS_URL: &str = "/APP/Timeline/unmutePlurks";
+pub(crate) const TIMELINE_FAVORITE_PLURKS_URL: &str = "/APP/Timeline/favoritePlurks";
+pub(crate) const TIMELINE_UNFAVORITE_PLURKS_URL: &str = "/APP/Timeline/unfavoritePlurks";
+pub(crate) const TIMELINE_REPLURK_URL: &str = "/APP/Timeline/replurk";
+pub(crate) const TIMELINE_UNREPLURK_URL: &str = "/APP/Timeline/unreplurk";
+pub(crate) const TIMELINE_MARK_AS_READ_URL: &str = "/APP/Timeline/markAsRead";
+pub(crate) const TIMELINE_TOGGLE_COMMENTS_URL: &str = "/APP/Timeline/toggleComments";
+pub(crate) const TIMELINE_GET_UNREAD_PLURKS_URL: &str = "/APP/Timeline/getUnreadPlurks";
+pub(crate) const TIMELINE_UPLOAD_PICTURE_URL: &str = "/APP/Timeline/uploadPicture";
+const TIMELINE_PAGE_LIMIT: usize = 30;
+const MUTE_BATCH_SIZE: usize = 50;
+/// Default pause between deletions in [`Plurk::bulk_delete`] when no rate limiter is
+/// installed.
+pub(crate) const BULK_DELETE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineFilter {
+    /// Only the authorized user's own plurks.
+    OnlyUser,
+    /// Only plurks the authorized user responded to.
+    OnlyResponded,
+    OnlyPrivate,
+    OnlyFavorite,
+}
+
+impl fmt::Display for TimelineFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OnlyUser => write!(f, "only_user"),
+            Self::OnlyResponded => write!(f, "only_responded"),
+            Self::OnlyPrivate => write!(f, "only_private"),
+            Self::OnlyFavorite => write!(f, "only_favorite"),
+        }
+    }
+}
+
+/// Parameters of `/APP/Timeline/getPlurks`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimelineOptions {
+    offset: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+    filter: Option<TimelineFilter>,
+}
+
+impl TimelineOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only return plurks older than `offset`.
+    pub fn offset(mut self, offset: DateTime<Utc>) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn filter(mut self, filter: TimelineFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(offset) = &self.offset {
+            query.push(("offset", time::format_offset(offset)));
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(filter) = self.filter {
+            query.push(("filter", filter.to_string()));
+        }
+        query
+    }
+}
+
+/// Builder for `/APP/Timeline/plurkAdd`, created by [`Plurk::plurk_add`].
+#[derive(Debug, Clone)]
+pub struct PlurkAddBuilder<'a> {
+    plurk: &'a Plurk,
+    content: Option<String>,
+    qualifier: Option<Qualifier>,
+    limited_to: Option<LimitedTo>,
+    no_comments: Option<CommentPermission>,
+    lang: Option<PlurkLang>,
+    anonymous: bool,
+}
+
+impl<'a> PlurkAddBuilder<'a> {
+    fn new(plurk: &'a Plurk) -> Self {
+        Self {
+            plurk,
+            content: None,
+            qualifier: None,
+            limited_to: None,
+            no_comments: None,
+            lang: None,
+            anonymous: false,
+        }
+    }
+
+    pub fn content<S: Into<String>>(mut self, content: S) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    pub fn qualifier(mut self, qualifier: Qualifier) -> Self {
+        self.qualifier = Some(qualifier);
+        self
+    }
+
+    /// Only show the plurk to an audience, e.g. `&[3, 5]`, [`LimitedTo::Friends`] or a
+    /// [`Clique`](crate::api::cliques::Clique). [`LimitedTo::Private`] and
+    /// [`LimitedTo::Cliques`] are resolved to ids with an extra request when sending.
+    pub fn limited_to<L: Into<LimitedTo>>(mut self, limited_to: L) -> Self {
+        self.limited_to = Some(limited_to.into());
+        self
+    }
+
+    pub fn no_comments(mut self, no_comments: CommentPermission) -> Self {
+        self.no_comments = Some(no_comments);
+        self
+    }
+
+    pub fn lang(mut self, lang: PlurkLang) -> Self {
+        self.lang = Some(lang);
+        self
+    }
+
+    /// Hide the owner of the plurk. Responses to it are anonymized by Plurk as well.
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
+
+    fn to_query(&self) -> Result<Vec<(&'static str, String)>, PlurkError> {
+        let content = self.content.as_deref().unwrap_or_default();
+        check_content(content)?;
+        let qualifier = self.qualifier.as_ref().map_or("", Qualifier::as_str);
+        if qualifier.is_empty() {
+            return Err(PlurkError::InvalidArgument(String::from(
+                "Qualifier is required",
+            )));
+        }
+
+        let mut query = vec![
+            ("content", content.to_string()),
+            ("qualifier", qualifier.to_string()),
+        ];
+        match &self.limited_to {
+            None | Some(LimitedTo::Public) => {}
+            Some(LimitedTo::Friends) => query.push(("limited_to", encode_ids(&[0]))),
+            Some(LimitedTo::Users(ids)) if ids.is_empty() => {
+                return Err(PlurkError::InvalidArgument(String::from(
+                    "limited_to is empty",
+                )))
+            }
+            Some(LimitedTo::Users(ids)) => query.push(("limited_to", encode_ids(ids))),
+            Some(limited_to) => {
+                return Err(PlurkError::InvalidArgument(format!(
+                    "limited_to {} is not resolved to ids",
+                    limited_to
+                )))
+            }
+        }
+        if let Some(no_comments) = self.no_comments {
+            query.push(("no_comments", no_comments.to_string()));
+        }
+        if let Some(lang) = &self.lang {
+            query.push(("lang", lang.to_string()));
+        }
+        if self.anonymous {
+            query.push(("anonymous", String::from("1")));
+        }
+        Ok(query)
+    }
+
+    /// Replace [`LimitedTo::Private`] with the id of the authorized user and
+    /// [`LimitedTo::Cliques`] with the ids of their members.
+    async fn resolve_limited_to(mut self) -> Result<PlurkAddBuilder<'a>, PlurkError> {
+        match &self.limited_to {
+            Some(LimitedTo::Private) => {
+                let me = self.plurk.me().await?;
+                self.limited_to = Some(LimitedTo::Users(vec![me.id]));
+            }
+            Some(LimitedTo::Cliques(names)) => {
+                let mut ids = Vec::new();
+                for name in names {
+                    for id in self.plurk.get_clique(name).await?.user_ids() {
+                        if !ids.contains(&id) {
+                            ids.push(id);
+                        }
+                    }
+                }
+                self.limited_to = Some(LimitedTo::Users(ids));
+            }
+            _ => {}
+        }
+        Ok(self)
+    }
+
+    pub async fn send(self) -> Result<PlurkPost, PlurkError> {
+        let builder = self.resolve_limited_to().await?;
+        let query = builder.to_query()?;
+        let content = builder.content.as_deref().unwrap_or_default();
+        let post = builder.plurk.call(TIMELINE_PLURK_ADD_URL, Some(query));
+        builder.plurk.guarded(None, content, post).await
+    }
+}
+
+/// Result of [`Plurk::get_plurk`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PlurkWithOwner {
+    pub plurk: PlurkPost,
+    /// The owner of the plurk.
+    pub user: PlurkUser,
+}
+
+/// An image for [`Plurk::upload_picture`], read from disk or already in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Picture {
+    Path(PathBuf),
+    Bytes { file_name: String, data: Vec<u8> },
+}
+
+impl Picture {
+    pub fn bytes<S: Into<String>>(file_name: S, data: Vec<u8>) -> Self {
+        Self::Bytes {
+            file_name: file_name.into(),
+            data,
+        }
+    }
+
+    pub(crate) async fn into_multipart(
+        self,
+        field: &'static str,
+    ) -> Result<multipart::Form, PlurkError> {
+        match self {
+            Self::Path(path) => Plurk::file_to_multipart((String::from(field), path)).await,
+            Self::Bytes { file_name, data } => {
+                let part = multipart::Part::bytes(data).file_name(file_name);
+                Ok(multipart::Form::new().part(field, part))
+            }
+        }
+    }
+}
+
+impl From<&Path> for Picture {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<PathBuf> for Picture {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&str> for Picture {
+    fn from(path: &str) -> Self {
+        Self::Path(PathBuf::from(path))
+    }
+}
+
+/// URLs of an image uploaded with [`Plurk::upload_picture`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UploadedPicture {
+    pub full: String,
+    pub thumbnail: String,
+}
+
+/// Outcome of [`Plurk::bulk_delete`].
+#[derive(Debug, Default)]
+pub struct BulkDeleteReport {
+    /// Whether the confirmation callback allowed the deletion. When `false` this is a
+    /// dry-run report and nothing was deleted.
+    pub confirmed: bool,
+    pub deleted: Vec<u64>,
+    pub failed: Vec<(u64, PlurkError)>,
+}
+
+/// Outcome of an endpoint applied to a list of plurk ids, e.g. [`Plurk::favorite_plurks`].
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub succeeded: Vec<u64>,
+    pub failed: Vec<(u64, PlurkError)>,
+}
+
+/// Per-id outcome of [`Plurk::replurk`] and [`Plurk::unreplurk`].
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplurkResult {
+    pub success: bool,
+    /// Plurk's `error` text, empty on success.
+    #[serde(default)]
+    pub error: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReplurkResults {
+    #[serde(default, deserialize_with = "id_map::deserialize")]
+    results: HashMap<u64, ReplurkResult>,
+}
+
+struct RangeState {
+    offset: DateTime<Utc>,
+    seen: HashSet<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TimelinePage {
+    #[serde(default)]
+    plurks: Vec<PlurkPost>,
+}
+
+/// Offset for the next `getPlurks` call, which returns plurks strictly older than it.
+///
+/// Timestamps only have second precision, so the next page starts one second after the
+/// oldest plurk to pick up others posted in the same second. When a page brought nothing
+/// new, step past that second instead, otherwise the same page would be fetched forever.
+fn next_timeline_offset(
+    page_len: usize,
+    fresh: usize,
+    oldest: DateTime<Utc>,
+    from: &DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if page_len < TIMELINE_PAGE_LIMIT || oldest < *from {
+        None
+    } else if fresh == 0 {
+        Some(oldest)
+    } else {
+        Some(oldest + Duration::seconds(1))
+    }
+}
+
+impl Plurk {
+    async fn get_timeline_page(
+        &self,
+        options: &TimelineOptions,
+    ) -> Result<TimelinePage, PlurkError> {
+        self.call(TIMELINE_GET_PLURKS_URL, Some(options.to_query()))
+            .await
+    }
+
+    /// Unread plurks of the timeline, newest first.
+    pub async fn get_unread_plurks(
+        &self,
+        filter: Option<TimelineFilter>,
+        limit: Option<usize>,
+    ) -> Result<Vec<PlurkPost>, PlurkError> {
+        let options = TimelineOptions {
+            offset: None,
+            limit,
+            filter,
+        };
+        let page: TimelinePage = self
+            .call(TIMELINE_GET_UNREAD_PLURKS_URL, Some(options.to_query()))
+            .await?;
+        Ok(page.plurks)
+    }
+
+    /// A single plurk and its owner, by numeric id or a [`PlurkId`], e.g. one parsed
+    /// with [`PlurkId::from_url`].
+    pub async fn get_plurk<I>(&self, plurk_id: I) -> Result<PlurkWithOwner, PlurkError>
+    where
+        I: Into<PlurkId>,
+    {
+        let plurk_id = plurk_id.into();
+        self.call(
+            TIMELINE_GET_PLURK_URL,
+            Some([("plurk_id", plurk_id.to_string())]),
+        )
+        .await
+    }
+
+    /// Plurks of the authorized user's timeline, newest first. Plurk returns at most
+    /// 30 per call unless `options` sets a smaller limit.
+    pub async fn get_plurks(&self, options: TimelineOptions) -> Result<Vec<PlurkPost>, PlurkError> {
+        Ok(self.get_timeline_page(&options).await?.plurks)
+    }
+
+    /// Upload an image, e.g. `plurk.upload_picture("cat.jpg")`, to include its URL in
+    /// a plurk or response.
+    pub async fn upload_picture<P>(&self, picture: P) -> Result<UploadedPicture, PlurkError>
+    where
+        P: Into<Picture>,
+    {
+        let form = picture.into().into_multipart("image").await?;
+        self.call_with_form(TIMELINE_UPLOAD_PICTURE_URL, form).await
+    }
+
+    pub(crate) async fn add_plurk(
+        &self,
+        content: &str,
+        qualifier: Qualifier,
+    ) -> Result<PlurkPost, PlurkError> {
+        self.plurk_add()
+            .content(content)
+            .qualifier(qualifier)
+            .send()
+            .await
+    }
+
+    pub fn plurk_add(&self) -> PlurkAddBuilder<'_> {
+        PlurkAddBuilder::new(self)
+    }
+
+    /// Upload `images`, append their URLs to `content` and post the result.
+    ///
+    /// Nothing is posted unless every upload succeeds and the final content fits in
+    /// [`MAX_CONTENT_LENGTH`].
+    pub async fn post_with_images<S, P>(
+        &self,
+        content: S,
+        qualifier: Qualifier,
+        images: &[P],
+    ) -> Result<PlurkPost, PlurkError>
+    where
+        S: AsRef<str>,
+        P: AsRef<Path>,
+    {
+        let content = content.as_ref();
+        check_image_room(content, images.len())?;
+
+        let mut urls = Vec::with_capacity(images.len());
+        for image in images {
+            urls.push(self.upload_picture(image.as_ref()).await?.full);
+        }
+
+        let content = append_image_urls(content, &urls);
+        if content.chars().count() > MAX_CONTENT_LENGTH {
+            return Err(content_too_long());
+        }
+        self.add_plurk(&content, qualifier).await
+    }
+
+    /// Replace the content of `plurk_id` and return the edited plurk.
+    pub async fn edit_plurk(&self, plurk_id: u64, content: &str) -> Result<PlurkPost, PlurkError> {
+        check_content(content)?;
+        self.call(
+            TIMELINE_PLURK_EDIT_URL,
+            Some([
+                ("plurk_id", plurk_id.to_string()),
+                ("content", content.to_string()),
+            ]),
+        )
+        .await
+    }
+
+    pub async fn set_comment_permission(
+        &self,
+        plurk_id: u64,
+        permission: CommentPermission,
+    ) -> Result<(), PlurkError> {
+        self.call_ok(
+            TIMELINE_TOGGLE_COMMENTS_URL,
+            Some([
+                ("plurk_id", plurk_id.to_string()),
+                ("no_comments", permission.to_string()),
+            ]),
+        )
+        .await
+    }
+
+    /// Delete one of the user's plurks. Plurk answers `success_text` on success, anything
+    /// else is returned as an error.
+    pub async fn delete_plurk(&self, plurk_id: u64) -> Result<(), PlurkError> {
+        self.call_ok(
+            TIMELINE_PLURK_DELETE_URL,
+            Some([("plurk_id", plurk_id.to_string())]),
+        )
+        .await
+    }
+
+    /// Delete `ids` one by one once `confirm` approves the full list.
+    ///
+    /// A failed deletion does not stop the others; it is recorded in the report. The
+    /// deletions are paced by the rate limiter when one is installed, otherwise by
+    /// [`Plurk::with_bulk_delete_delay`].
+    pub async fn bulk_delete<F>(&self, ids: &[u64], confirm: F) -> BulkDeleteReport
+    where
+        F: FnOnce(&[u64]) -> bool,
+    {
+        let mut report = BulkDeleteReport {
+            confirmed: confirm(ids),
+            ..Default::default()
+        };
+        if !report.confirmed {
+            return report;
+        }
+
+        for (i, &plurk_id) in ids.iter().enumerate() {
+            if i > 0 && self.rate_limiter.is_none() {
+                tokio::time::sleep(self.bulk_delete_delay).await;
+            }
+            match self.delete_plurk(plurk_id).await {
+                Ok(()) => report.deleted.push(plurk_id),
+                Err(e) => report.failed.push((plurk_id, e)),
+            }
+        }
+        report
+    }
+
+    /// Pause between deletions of [`Self::bulk_delete`], 500ms by default. Ignored when a
+    /// rate limiter is installed.
+    pub fn with_bulk_delete_delay(mut self, delay: std::time::Duration) -> Self {
+        self.bulk_delete_delay = delay;
+        self
+    }
+
+    /// Call an `ids` endpoint for all of `ids` at once. Plurk only answers with a single
+    /// success or error, so when the batch fails each id is retried alone to find out
+    /// which ones are at fault.
+    async fn batch_ids(&self, url: &'static str, ids: &[u64]) -> BatchReport {
+        let mut report = BatchReport::default();
+        if ids.is_empty() {
+            return report;
+        }
+        let res = self.call_ok(url, Some([("ids", encode_ids(ids))])).await;
+        match res {
+            Ok(()) => report.succeeded = ids.to_vec(),
+            Err(e) if ids.len() == 1 => report.failed.push((ids[0], e)),
+            Err(_) => {
+                for &id in ids {
+                    match self.call_ok(url, Some([("ids", encode_ids(&[id]))])).await {
+                        Ok(()) => report.succeeded.push(id),
+                        Err(e) => report.failed.push((id, e)),
+                    }
+                }
+            }
+        }
+        report
+    }
+
+    pub async fn favorite_plurks(&self, ids: &[u64]) -> BatchReport {
+        self.batch_ids(TIMELINE_FAVORITE_PLURKS_URL, ids).await
+    }
+
+    pub async fn unfavorite_plurks(&self, ids: &[u64]) -> BatchReport {
+        self.batch_ids(TIMELINE_UNFAVORITE_PLURKS_URL, ids).await
+    }
+
+    /// Replurk `ids`, returning the outcome of each.
+    pub async fn replurk(&self, ids: &[u64]) -> Result<HashMap<u64, ReplurkResult>, PlurkError> {
+        let res: ReplurkResults = self
+            .call(TIMELINE_REPLURK_URL, Some([("ids", encode_ids(ids))]))
+            .await?;
+        Ok(res.results)
+    }
+
+    pub async fn unreplurk(&self, ids: &[u64]) -> Result<HashMap<u64, ReplurkResult>, PlurkError> {
+        let res: ReplurkResults = self
+            .call(TIMELINE_UNREPLURK_URL, Some([("ids", encode_ids(ids))]))
+            .await?;
+        Ok(res.results)
+    }
+
+    /// Mark `ids` as read. With `note_position` Plurk also remembers how far the
+    /// responses of each plurk were read.
+    pub async fn mark_as_read(&self, ids: &[u64], note_position: bool) -> Result<(), PlurkError> {
+        self.call_ok(
+            TIMELINE_MARK_AS_READ_URL,
+            Some([
+                ("ids", encode_ids(ids)),
+                ("note_position", note_position.to_string()),
+            ]),
+        )
+        .await
+    }
+
+    /// Stop notifications for new responses on `ids`.
+    pub async fn mute_plurks(&self, ids: &[u64]) -> Result<(), PlurkError> {
+        self.call_ok(TIMELINE_MUTE_PLURKS_URL, Some([("ids", encode_ids(ids))]))
+            .await
+    }
+
+    pub async fn unmute_plurks(&self, ids: &[u64]) -> Result<(), PlurkError> {
+        self.call_ok(TIMELINE_UNMUTE_PLURKS_URL, Some([("ids", encode_ids(ids))]))
+            .await
+    }
+
+    /// Mute every plurk by `user_id` on the timeline posted since `since`, e.g.
+    /// `Utc::now() - Duration::days(7)`, or on the whole timeline if `None`. The timeline
+    /// is paged 30 plurks at a time, so an older `since` costs more calls.
+    ///
+    /// A failing batch does not stop the others; the report lists every id muted so far
+    /// and the ones that failed. Only fetching the timeline is an error.
+    pub async fn mute_plurks_by(
+        &self,
+        user_id: u64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<BatchReport, PlurkError> {
+        let ids: Vec<u64> = self
+            .get_plurks_between(since.unwrap_or(DateTime::<Utc>::MIN_UTC), Utc::now(), None)
+            .await?
+            .into_iter()
+            .filter(|p| p.owner_id == user_id)
+            .map(|p| p.plurk_id)
+            .collect();
+
+        let mut report = BatchReport::default();
+        for batch in ids.chunks(MUTE_BATCH_SIZE) {
+            let res = self.batch_ids(TIMELINE_MUTE_PLURKS_URL, batch).await;
+            report.succeeded.extend(res.succeeded);
+            report.failed.extend(res.failed);
+        }
+        Ok(report)
+    }
+
+    /// Pages of timeline plurks posted between `from` and `to` (both inclusive), newest
+    /// page first.
+    pub(crate) fn timeline_range_pages(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        filter: Option<TimelineFilter>,
+    ) -> impl Stream<Item = Result<Vec<PlurkPost>, PlurkError>> + '_ {
+        let init = (from <= to).then(|| RangeState {
+            offset: to + Duration::seconds(1),
+            seen: HashSet::new(),
+        });
+        stream::try_unfold(init, move |state| async move {
+            let Some(mut state) = state else {
+                return Ok::<_, PlurkError>(None);
+            };
+            let options = TimelineOptions {
+                offset: Some(state.offset),
+                limit: Some(TIMELINE_PAGE_LIMIT),
+                filter,
+            };
+            let page = self.get_timeline_page(&options).await?;
+            let page_len = page.plurks.len();
+            let Some(oldest) = page.plurks.iter().map(|p| p.posted).min() else {
+                return Ok(None);
+            };
+
+            let mut fresh = 0;
+            let mut plurks = Vec::new();
+            for plurk in page.plurks {
+                if !state.seen.insert(plurk.plurk_id) {
+                    continue;
+                }
+                fresh += 1;
+                if (from..=to).contains(&plurk.posted) {
+                    plurks.push(plurk);
+                }
+            }
+
+            let next =
+                next_timeline_offset(page_len, fresh, oldest, &from).map(|offset| RangeState {
+                    offset,
+                    seen: state.seen,
+                });
+            Ok(Some((plurks, next)))
+        })
+    }
+
+    /// Stream the whole timeline, newest first, starting with the plurks posted before
+    /// `offset` (now if `None`). Pages are only fetched as the stream is polled, so
+    /// `.take(n)` stops after the pages needed for `n` plurks.
+    pub fn get_plurks_stream(
+        &self,
+        offset: Option<DateTime<Utc>>,
+        filter: Option<TimelineFilter>,
+    ) -> impl Stream<Item = Result<PlurkPost, PlurkError>> + '_ {
+        let to = offset.map_or_else(Utc::now, |t| t - Duration::seconds(1));
+        self.timeline_range_pages(DateTime::<Utc>::MIN_UTC, to, filter)
+            .map_ok(|plurks| stream::iter(plurks.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Every timeline plurk posted between `from` and `to` (both inclusive), oldest first.
+    pub async fn get_plurks_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        filter: Option<TimelineFilter>,
+    ) -> Result<Vec<PlurkPost>, PlurkError> {
+        let mut plurks: Vec<PlurkPost> = self
+            .timeline_range_pages(from, to, filter)
+            .try_concat()
+            .await?;
+        plurks.sort_by_key(|p| p.posted);
+        Ok(plurks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_timeline_offset() {
+        let from = Utc.with_ymd_and_hms(2009, 6, 1, 0, 0, 0).unwrap();
+        let oldest = Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, 13).unwrap();
+
+        assert_eq!(
+            next_timeline_offset(TIMELINE_PAGE_LIMIT, 5, oldest, &from),
+            Some(oldest + Duration::seconds(1))
+        );
+        assert_eq!(
+            next_timeline_offset(TIMELINE_PAGE_LIMIT, 0, oldest, &from),
+            Some(oldest)
+        );
+        // Last page of the timeline
+        assert_eq!(next_timeline_offset(3, 3, oldest, &from), None);
+        // Went past the start of the range
+        let before = Utc.with_ymd_and_hms(2009, 5, 31, 0, 0, 0).unwrap();
+        assert_eq!(
+            next_timeline_offset(TIMELINE_PAGE_LIMIT, 5, before, &from),
+            None
+        );
+    }
+
+    #[test]
+    fn test_options_query() {
+        assert!(TimelineOptions::new().to_query().is_empty());
+
+        let offset = Utc.with_ymd_and_hms(2009, 6, 20, 21, 55, 34).unwrap();
+        let query = TimelineOptions::new()
+            .offset(offset)
+            .limit(10)
+            .filter(TimelineFilter::OnlyFavorite)
+            .to_query();
+        assert_eq!(
+            query,
+            [
+                ("offset", String::from("2009-6-20T21:55:34")),
+                ("limit", String::from("10")),
+                ("filter", String::from("only_favorite")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plurk_add_query() {
+        let plurk = Plurk::new("c1", "c2", None, None);
+        assert!(plurk
+            .plurk_add()
+            .qualifier(Qualifier::Freestyle)
+            .to_query()
+            .is_err());
+        assert!(plurk
+            .plurk_add()
+            .content(" ")
+            .qualifier(Qualifier::Freestyle)
+            .to_query()
+            .is_err());
+        assert!(plurk.plurk_add().content("hi").to_query().is_err());
+        let too_long = "a".repeat(MAX_CONTENT_LENGTH + 1);
+        assert!(plurk
+            .plurk_add()
+            .content(too_long)
+            .qualifier(Qualifier::Freestyle)
+            .to_query()
+            .is_err());
+        assert!(plurk
+            .plurk_add()
+            .content("hi")
+            .qualifier(Qualifier::Freestyle)
+            .limited_to(&[])
+            .to_query()
+            .is_err());
+
+        let query = plurk
+            .plurk_add()
+            .content("hi")
+            .qualifier(Qualifier::Says)
+            .limited_to(&[3, 5])
+            .no_comments(CommentPermission::OnlyFriends)
+            .lang(PlurkLang::TraditionalChinese)
+            .anonymous(true)
+            .to_query()
+            .unwrap();
+        assert_eq!(
+            query,
+            [
+                ("content", String::from("hi")),
+                ("qualifier", String::from("says")),
+                ("limited_to", String::from("[3,5]")),
+                ("no_comments", String::from("2")),
+                ("lang", String::from("tr_ch")),
+                ("anonymous", String::from("1")),
+            ]
+        );
+
+        let query = plurk
+            .plurk_add()
+            .content("hi")
+            .qualifier(Qualifier::Freestyle)
+            .limited_to(LimitedTo::Friends)
+            .to_query()
+            .unwrap();
+        assert_eq!(query[2], ("limited_to", String::from("[0]")));
+        assert!(plurk
+            .plurk_add()
+            .content("hi")
+            .qualifier(Qualifier::Freestyle)
+            .limited_to(LimitedTo::Private)
+            .to_query()
+            .is_err());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_plurk_add_resolve_limited_to() {
+        use crate::testing::{fixtures, MockServer};
+
+        let server = MockServer::new()
+            .mock("/APP/Users/me", fixtures::USERS_ME)
+            .mock(
+                "/APP/Cliques/getClique",
+                r#"[{"id": 5, "nick_name": "amber"}, {"id": 7, "nick_name": "bob"}]"#,
+            )
+            .mock(TIMELINE_PLURK_ADD_URL, fixtures::PLURK_ADD);
+        let plurk = server.plurk();
+        let add = || plurk.plurk_add().content("hi").qualifier(Qualifier::Says);
+
+        add().limited_to(LimitedTo::Private).send().await.unwrap();
+        let cliques = vec![String::from("Family"), String::from("Work")];
+        add()
+            .limited_to(LimitedTo::Cliques(cliques))
+            .send()
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[1].param("limited_to"), Some("[3]"));
+        assert_eq!(requests[3].param("clique_name"), Some("Work"));
+        assert_eq!(requests[4].param("limited_to"), Some("[5,7]"));
+    }
+
+    #[tokio::test]
+    async fn test_picture() {
+        assert_eq!(
+            Picture::from("cat.jpg"),
+            Picture::Path(PathBuf::from("cat.jpg"))
+        );
+        assert!(Picture::from("missing.jpg")
+            .into_multipart("image")
+            .await
+            .is_err());
+        assert!(Picture::bytes("cat.jpg", b"\xff\xd8".to_vec())
+            .into_multipart("image")
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_batch_ids_empty() {
+        let plurk = Plurk::new("c1", "c2", Some("t1"), Some("t2"));
+        let report = plurk.favorite_plurks(&[]).await;
+        assert!(report.succeeded.is_empty());
+        assert!(report.failed.is_empty());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_mute_plurks_by() {
+        use crate::testing::{fixtures, MockServer};
+
+        let server = MockServer::new()
+            .mock(TIMELINE_GET_PLURKS_URL, fixtures::GET_PLURKS)
+            .mock(TIMELINE_MUTE_PLURKS_URL, fixtures::SUCCESS);
+        let report = server.plurk().mute_plurks_by(3, None).await.unwrap();
+        assert_eq!(report.succeeded, [1_495_754_904]);
+        assert!(report.failed.is_empty());
+
+        let since = Utc.with_ymd_and_hms(2009, 6, 5, 23, 0, 0).unwrap();
+        let report = server.plurk().mute_plurks_by(5, Some(since)).await.unwrap();
+        assert!(report.succeeded.is_empty());
+
+        let server = MockServer::new()
+            .mock(TIMELINE_GET_PLURKS_URL, fixtures::GET_PLURKS)
+            .mock_status(TIMELINE_MUTE_PLURKS_URL, 400, fixtures::PLURK_NOT_FOUND);
+        let report = server.plurk().mute_plurks_by(5, None).await.unwrap();
+        assert!(report.succeeded.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, 1_495_754_903);
+    }
+
+    #[tokio::test]
+    async fn test_edit_plurk_empty() {
+        let plurk = Plurk::new("c1", "c2", Some("t1"), Some("t2"));
+        let res = plurk.edit_plurk(3, " ").await;
+        assert!(matches!(res, Err(PlurkError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_post_with_images_too_long() {
+        let plurk = Plurk::new("c1", "c2", Some("t1"), Some("t2"));
+        // Fits the old one character per URL estimate, but not a real image URL.
+        let content = "a".repeat(MAX_CONTENT_LENGTH - 10);
+        let res = plurk
+            .post_with_images(&content, Qualifier::Freestyle, &["missing.jpg"])
+            .await;
+        assert!(matches!(res, Err(PlurkError::InvalidArgument(_))));
+
+        // Short enough, so the upload is attempted and fails on the missing file.
+        let res = plurk
+            .post_with_images("look", Qualifier::Freestyle, &["missing.jpg"])
+            .await;
+        assert!(matches!(res, Err(PlurkError::APICallError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_delete_dry_run() {
+        let plurk = Plurk::new("c1", "c2", Some("t1"), Some("t2"));
+        let mut asked = Vec::new();
+        let report = plurk
+            .bulk_delete(&[1, 2, 3], |ids| {
+                asked.extend_from_slice(ids);
+                false
+            })
+            .await;
+        assert_eq!(asked, [1, 2, 3]);
+        assert!(!report.confirmed);
+        assert!(report.deleted.is_empty());
+        assert!(report.failed.is_empty());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test(start_paused = true)]
+    async fn test_bulk_delete_delay() {
+        use crate::rate_limit::RateLimiter;
+        use crate::testing::MockServer;
+        use std::time::Duration;
+        use tokio::time::Instant;
+
+        let server = MockServer::new().mock(TIMELINE_PLURK_DELETE_URL, r#"{"success_text": "ok"}"#);
+        let plurk = server
+            .plurk()
+            .with_bulk_delete_delay(Duration::from_millis(100));
+        let start = Instant::now();
+        let report = plurk.bulk_delete(&[1, 2, 3], |_| true).await;
+        assert_eq!(report.deleted, [1, 2, 3]);
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+
+        let plurk = plurk.with_rate_limiter(RateLimiter::new(100, Duration::from_secs(1)));
+        let start = Instant::now();
+        let report = plurk.bulk_delete(&[1, 2, 3], |_| true).await;
+        assert_eq!(report.deleted, [1, 2, 3]);
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(server.requests().len(), 6);
+    }
+
+    #[test]
+    fn test_deserialize_plurk_with_owner() {
+        let raw = r#"{
+            "plurk": {
+                "plurk_id": 3, "owner_id": 3, "content": "hi", "content_raw": "hi",
+                "qualifier": "says", "posted": "Fri, 05 Jun 2009 23:07:13 GMT"
+            },
+            "user": {"id": 3, "nick_name": "alvin"}
+        }"#;
+        let res: PlurkWithOwner = serde_json::from_str(raw).unwrap();
+        assert_eq!(res.plurk.plurk_id, 3);
+        assert_eq!(res.user.nick_name, "alvin");
+    }
+
+    #[test]
+    fn test_deserialize_replurk_results() {
+        let raw = r#"{
+            "success": false,
+            "results": {
+                "3": {"success": true, "error": ""},
+                "5": {"success": false, "error": "Plurk not found"}
+            }
+        }"#;
+        let res: ReplurkResults = serde_json::from_str(raw).unwrap();
+        assert!(res.results[&3].success);
+        assert_eq!(res.results[&5].error, "Plurk not found");
+    }
+
+    #[test]
+    fn test_deserialize_page() {
+        let raw = r#"{
+            "plurks": [{
+                "plurk_id": 3, "owner_id": 3, "content": "hi", "content_raw": "hi",
+                "qualifier": "says", "posted": "Fri, 05 Jun 2009 23:07:13 GMT"
+            }],
+            "plurk_users": {"3": {"id": 3, "nick_name": "alvin"}}
+        }"#;
+        let page: TimelinePage = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.plurks[0].plurk_id, 3);
+    }
+}
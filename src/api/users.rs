@@ -0,0 +1,234 @@
+use crate::api::timeline::Picture;
+use crate::models::{PlurkUser, Privacy};
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+
+pub(crate) const USERS_ME_URL: &str = "/APP/Users/me";
+pub(crate) const USERS_UPDATE_URL: &str = "/APP/Users/update";
+pub(crate) const USERS_UPDATE_AVATAR_URL: &str = "/APP/Users/updateAvatar";
+pub(crate) const USERS_GET_KARMA_STATS_URL: &str = "/APP/Users/getKarmaStats";
+
+/// Result of [`Plurk::get_karma_stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KarmaStats {
+    pub current_karma: f64,
+    /// Why karma dropped recently, empty if it did not.
+    pub karma_fall_reason: String,
+    /// URL of a karma chart image.
+    pub karma_graph: String,
+    /// Karma over time, oldest first.
+    pub karma_trend: Vec<(DateTime<Utc>, f64)>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawKarmaStats {
+    #[serde(default)]
+    current_karma: f64,
+    #[serde(default)]
+    karma_fall_reason: String,
+    #[serde(default)]
+    karma_graph: String,
+    #[serde(default)]
+    karma_trend: Vec<String>,
+}
+
+/// Parse a `karma_trend` entry, a `"<unix timestamp>-<karma>"` string.
+fn parse_trend(entry: &str) -> Result<(DateTime<Utc>, f64), PlurkError> {
+    let invalid = || PlurkError::APICallError(format!("Invalid karma trend: {}", entry));
+    let (timestamp, karma) = entry.split_once('-').ok_or_else(invalid)?;
+    let timestamp = timestamp.parse().map_err(|_| invalid())?;
+    let time = Utc
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .ok_or_else(invalid)?;
+    let karma = karma.parse().map_err(|_| invalid())?;
+    Ok((time, karma))
+}
+
+impl TryFrom<RawKarmaStats> for KarmaStats {
+    type Error = PlurkError;
+
+    fn try_from(raw: RawKarmaStats) -> Result<Self, Self::Error> {
+        Ok(Self {
+            current_karma: raw.current_karma,
+            karma_fall_reason: raw.karma_fall_reason,
+            karma_graph: raw.karma_graph,
+            karma_trend: raw
+                .karma_trend
+                .iter()
+                .map(|entry| parse_trend(entry))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Builder for `/APP/Users/update`, created by [`Plurk::update_user`].
+#[derive(Debug, Clone)]
+pub struct UserUpdateBuilder<'a> {
+    plurk: &'a Plurk,
+    full_name: Option<String>,
+    email: Option<String>,
+    display_name: Option<String>,
+    privacy: Option<Privacy>,
+    date_of_birth: Option<NaiveDate>,
+}
+
+impl<'a> UserUpdateBuilder<'a> {
+    fn new(plurk: &'a Plurk) -> Self {
+        Self {
+            plurk,
+            full_name: None,
+            email: None,
+            display_name: None,
+            privacy: None,
+            date_of_birth: None,
+        }
+    }
+
+    pub fn full_name<S: Into<String>>(mut self, full_name: S) -> Self {
+        self.full_name = Some(full_name.into());
+        self
+    }
+
+    pub fn email<S: Into<String>>(mut self, email: S) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    pub fn display_name<S: Into<String>>(mut self, display_name: S) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    pub fn privacy(mut self, privacy: Privacy) -> Self {
+        self.privacy = Some(privacy);
+        self
+    }
+
+    pub fn date_of_birth(mut self, date_of_birth: NaiveDate) -> Self {
+        self.date_of_birth = Some(date_of_birth);
+        self
+    }
+
+    fn to_query(&self) -> Result<Vec<(&'static str, String)>, PlurkError> {
+        let mut query = Vec::new();
+        if let Some(full_name) = &self.full_name {
+            query.push(("full_name", full_name.clone()));
+        }
+        if let Some(email) = &self.email {
+            if !email.contains('@') {
+                return Err(PlurkError::InvalidArgument(format!(
+                    "Invalid email: {}",
+                    email
+                )));
+            }
+            query.push(("email", email.clone()));
+        }
+        if let Some(display_name) = &self.display_name {
+            query.push(("display_name", display_name.clone()));
+        }
+        if let Some(privacy) = self.privacy {
+            query.push(("privacy", privacy.to_string()));
+        }
+        if let Some(date_of_birth) = self.date_of_birth {
+            query.push((
+                "date_of_birth",
+                date_of_birth.format("%Y-%m-%d").to_string(),
+            ));
+        }
+        if query.is_empty() {
+            return Err(PlurkError::InvalidArgument(String::from(
+                "Nothing to update",
+            )));
+        }
+        Ok(query)
+    }
+
+    pub async fn send(self) -> Result<(), PlurkError> {
+        let query = self.to_query()?;
+        self.plurk.call_ok(USERS_UPDATE_URL, Some(query)).await
+    }
+}
+
+impl Plurk {
+    /// The authorized user, a cheap way to check that the tokens still work.
+    pub async fn me(&self) -> Result<PlurkUser, PlurkError> {
+        self.call(USERS_ME_URL, None::<()>).await
+    }
+
+    pub async fn get_karma_stats(&self) -> Result<KarmaStats, PlurkError> {
+        let raw: RawKarmaStats = self.call(USERS_GET_KARMA_STATS_URL, None::<()>).await?;
+        raw.try_into()
+    }
+
+    /// Replace the profile image and return the updated user.
+    pub async fn update_avatar<P>(&self, picture: P) -> Result<PlurkUser, PlurkError>
+    where
+        P: Into<Picture>,
+    {
+        let form = picture.into().into_multipart("profile_image").await?;
+        self.call_with_form(USERS_UPDATE_AVATAR_URL, form).await
+    }
+
+    pub fn update_user(&self) -> UserUpdateBuilder<'_> {
+        UserUpdateBuilder::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_karma_stats() {
+        let raw = r#"{
+            "karma_trend": ["1282046402-97.85", "1282132802-98.1"],
+            "karma_fall_reason": "",
+            "current_karma": 98.1,
+            "karma_graph": "https://www.plurk.com/karma_graph.png"
+        }"#;
+        let raw: RawKarmaStats = serde_json::from_str(raw).unwrap();
+        let stats = KarmaStats::try_from(raw).unwrap();
+        assert_eq!(stats.current_karma, 98.1);
+        assert_eq!(
+            stats.karma_trend[0],
+            (Utc.timestamp_opt(1282046402, 0).unwrap(), 97.85)
+        );
+        assert_eq!(stats.karma_trend.len(), 2);
+        assert!(parse_trend("97.85").is_err());
+        assert!(parse_trend("x-97.85").is_err());
+    }
+
+    #[test]
+    fn test_update_query() {
+        let plurk = Plurk::new("c1", "c2", None, None);
+        assert!(plurk.update_user().to_query().is_err());
+
+        let query = plurk
+            .update_user()
+            .privacy(Privacy::OnlyFriends)
+            .to_query()
+            .unwrap();
+        assert_eq!(query, [("privacy", String::from("only_friends"))]);
+
+        assert!(plurk.update_user().email("alvin").to_query().is_err());
+        let query = plurk
+            .update_user()
+            .full_name("Alvin")
+            .email("alvin@example.com")
+            .display_name("amix")
+            .date_of_birth(NaiveDate::from_ymd_opt(1985, 2, 3).unwrap())
+            .to_query()
+            .unwrap();
+        assert_eq!(
+            query,
+            [
+                ("full_name", String::from("Alvin")),
+                ("email", String::from("alvin@example.com")),
+                ("display_name", String::from("amix")),
+                ("date_of_birth", String::from("1985-02-03")),
+            ]
+        );
+    }
+}
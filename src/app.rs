@@ -1,5 +1,5 @@
 use clap::Parser;
-use reqwest::StatusCode;
+use rust_plurk::api::coverage::coverage;
 use rust_plurk::plurk::{Plurk, PlurkError};
 use serde::{Deserialize, Serialize};
 use std::io::{self, Write};
@@ -30,8 +30,12 @@ struct Cli {
     key_file: Option<String>,
 
     /// API Path
-    #[arg(short = 'i', long)]
-    api: String,
+    #[arg(short = 'i', long, required_unless_present = "endpoints")]
+    api: Option<String>,
+
+    /// Print the known API paths, one per line, and exit. Used for shell completion.
+    #[arg(long)]
+    endpoints: bool,
 
     /// Optional argument with file path. Format: -f "key,path"
     #[arg(short = 'f', long)]
@@ -58,6 +62,14 @@ pub struct OauthKeys {
 async fn main() -> Result<(), PlurkError> {
     let cli = Cli::parse();
 
+    if cli.endpoints {
+        for endpoint in coverage() {
+            println!("{}", endpoint.path);
+        }
+        return Ok(());
+    }
+    let api = cli.api.unwrap_or_default();
+
     let plurk = match (cli.consumer_key, cli.consumer_secret, cli.key_file.clone()) {
         (Some(consumer_key), Some(consumer_secret), None) => Plurk::new(
             consumer_key,
@@ -74,18 +86,22 @@ async fn main() -> Result<(), PlurkError> {
 
     let plurk = if !plurk.is_auth() {
         let mut plurk = plurk;
-        plurk.request_auth().await?;
-        let url = plurk.get_auth_url()?;
-        println!("Please access to: {}", url);
-        print!("Input pin:");
-        io::stdout().flush().expect("Flush failed");
-
-        let mut user_input = String::new();
-        io::stdin()
-            .read_line(&mut user_input)
-            .expect("Failed to read the user input");
-        let pin = user_input.trim();
-        plurk.verify_auth(pin).await?;
+        plurk
+            .authorize_interactive(
+                |url| {
+                    println!("Please access to: {}", url);
+                    print!("Input pin:");
+                    io::stdout().flush().expect("Flush failed");
+                },
+                async {
+                    let mut user_input = String::new();
+                    io::stdin()
+                        .read_line(&mut user_input)
+                        .expect("Failed to read the user input");
+                    user_input
+                },
+            )
+            .await?;
         plurk
     } else {
         plurk
@@ -116,25 +132,19 @@ async fn main() -> Result<(), PlurkError> {
         )
     });
 
-    let res = plurk.request(cli.api, parameters, file_parameters).await?;
-
-    match res.status() {
-        StatusCode::OK => (),
-        StatusCode::BAD_REQUEST => {
-            println!("Error: {}", &res.status());
-        }
-        _ => {
-            println!("Error: {}", &res.status());
-            return Ok(());
-        }
-    }
-
-    if res.headers()["content-type"] != "application/json" {
-        println!("Response is not json type. Maybe call the wrong API or Oauth error.");
-        return Ok(());
-    }
-
-    let parsed_res: serde_json::Value = res.json().await.expect("To json failed.");
+    let parsed_res: serde_json::Value =
+        match plurk.request_json(api, parameters, file_parameters).await {
+            Ok(res) => res,
+            Err(PlurkError::Api { code, text }) => {
+                println!("Error: {}: {}", code, text);
+                return Ok(());
+            }
+            Err(PlurkError::APICallError(e)) => {
+                println!("{}. Maybe call the wrong API or Oauth error.", e);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
 
     let pretty = serde_json::to_string_pretty(&parsed_res).expect("Format json failed.");
     println!("{}", pretty);
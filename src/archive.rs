@@ -0,0 +1,327 @@
+use crate::api::timeline::TimelineFilter;
+use crate::export::{
+    io_error, Manifest, FRIENDS_PATH, MANIFEST_PATH, PLURKS_PATH, PROFILE_PATH, RESPONSES_PATH,
+};
+use crate::models::{PlurkPost, PlurkResponse, PlurkUser};
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+fn parse_error(path: &str, e: serde_json::Error) -> PlurkError {
+    PlurkError::IOError(format!("{}: {}", path, e))
+}
+
+fn open_region(
+    path: &Path,
+    entries: &HashMap<String, (u64, u64)>,
+    name: &str,
+) -> Result<BufReader<io::Take<File>>, PlurkError> {
+    let &(offset, size) = entries
+        .get(name)
+        .ok_or_else(|| PlurkError::IOError(format!("{}: not in archive", name)))?;
+    let mut file = File::open(path).map_err(io_error)?;
+    file.seek(SeekFrom::Start(offset)).map_err(io_error)?;
+    Ok(BufReader::new(file.take(size)))
+}
+
+/// Read access to an archive written by [`Plurk::export`](crate::plurk::Plurk::export).
+///
+/// Entries are located once when opening and read lazily from disk afterwards, so the
+/// archive is never loaded into memory as a whole.
+#[derive(Debug, Clone)]
+pub struct ArchiveReader {
+    path: PathBuf,
+    /// Archive path -> (data offset, size)
+    entries: HashMap<String, (u64, u64)>,
+    manifest: Manifest,
+}
+
+impl ArchiveReader {
+    pub fn open<P>(path: P) -> Result<Self, PlurkError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let mut archive = tar::Archive::new(File::open(&path).map_err(io_error)?);
+        let mut entries = HashMap::new();
+        for entry in archive.entries().map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            let name = entry.path().map_err(io_error)?.display().to_string();
+            entries.insert(name, (entry.raw_file_position(), entry.size()));
+        }
+
+        let mut raw = Vec::new();
+        open_region(&path, &entries, MANIFEST_PATH)?
+            .read_to_end(&mut raw)
+            .map_err(io_error)?;
+        let manifest = serde_json::from_slice(&raw).map_err(|e| parse_error(MANIFEST_PATH, e))?;
+
+        Ok(Self {
+            path,
+            entries,
+            manifest,
+        })
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Raw reader over a single archive entry, e.g. one of the `media/` images.
+    pub fn open_entry(&self, name: &str) -> Result<impl Read, PlurkError> {
+        open_region(&self.path, &self.entries, name)
+    }
+
+    /// Paths of all entries listed in the manifest under `media/`.
+    pub fn media(&self) -> impl Iterator<Item = &str> {
+        self.manifest
+            .entries
+            .iter()
+            .map(|e| e.path.as_str())
+            .filter(|p| p.starts_with("media/"))
+    }
+
+    pub fn profile(&self) -> Result<PlurkUser, PlurkError> {
+        let mut raw = Vec::new();
+        self.open_entry(PROFILE_PATH)?
+            .read_to_end(&mut raw)
+            .map_err(io_error)?;
+        serde_json::from_slice(&raw).map_err(|e| parse_error(PROFILE_PATH, e))
+    }
+
+    pub fn plurks(&self) -> Result<JsonLines<PlurkPost>, PlurkError> {
+        self.json_lines(PLURKS_PATH)
+    }
+
+    pub fn responses(&self) -> Result<JsonLines<PlurkResponse>, PlurkError> {
+        self.json_lines(RESPONSES_PATH)
+    }
+
+    pub fn friends(&self) -> Result<JsonLines<PlurkUser>, PlurkError> {
+        self.json_lines(FRIENDS_PATH)
+    }
+
+    fn json_lines<T>(&self, name: &'static str) -> Result<JsonLines<T>, PlurkError> {
+        Ok(JsonLines {
+            name,
+            lines: Box::new(BufReader::new(self.open_entry(name)?).lines()),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Check every entry against the size and SHA-256 recorded in the manifest.
+    pub fn verify(&self) -> Result<(), PlurkError> {
+        for expected in &self.manifest.entries {
+            let mut entry = self.open_entry(&expected.path)?;
+            let mut hasher = Sha256::new();
+            let size = io::copy(&mut entry, &mut hasher).map_err(io_error)?;
+            let digest: String = hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            if size != expected.size || digest != expected.sha256 {
+                return Err(PlurkError::IOError(format!(
+                    "{}: checksum mismatch",
+                    expected.path
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over a JSON lines entry of an archive.
+pub struct JsonLines<T> {
+    name: &'static str,
+    lines: Box<dyn Iterator<Item = io::Result<String>>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Iterator for JsonLines<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, PlurkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(io_error(e))),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line).map_err(|e| parse_error(self.name, e)));
+        }
+    }
+}
+
+/// Differences between archived plurks and the live timeline, see [`Plurk::diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimelineDiff {
+    /// Archived plurks no longer on the timeline.
+    pub deleted: Vec<PlurkPost>,
+    /// Archived plurks paired with their current, edited version.
+    pub edited: Vec<(PlurkPost, PlurkPost)>,
+}
+
+impl TimelineDiff {
+    pub fn is_empty(&self) -> bool {
+        self.deleted.is_empty() && self.edited.is_empty()
+    }
+}
+
+fn raw_content(plurk: &PlurkPost) -> &str {
+    if plurk.content_raw.is_empty() {
+        &plurk.content
+    } else {
+        &plurk.content_raw
+    }
+}
+
+fn diff_plurks(local: Vec<PlurkPost>, remote: Vec<PlurkPost>) -> TimelineDiff {
+    let mut remote: HashMap<u64, PlurkPost> = remote.into_iter().map(|p| (p.plurk_id, p)).collect();
+    let mut diff = TimelineDiff::default();
+    for plurk in local {
+        match remote.remove(&plurk.plurk_id) {
+            None => diff.deleted.push(plurk),
+            Some(live) if raw_content(&live) != raw_content(&plurk) => {
+                diff.edited.push((plurk, live))
+            }
+            Some(_) => {}
+        }
+    }
+    diff
+}
+
+impl Plurk {
+    /// Compare the plurks archived in `archive` that were posted between `from` and `to`
+    /// (inclusive) with the live timeline, reporting those deleted or edited since.
+    pub async fn diff(
+        &self,
+        archive: &ArchiveReader,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<TimelineDiff, PlurkError> {
+        let mut local = Vec::new();
+        for plurk in archive.plurks()? {
+            let plurk = plurk?;
+            if (from..=to).contains(&plurk.posted) {
+                local.push(plurk);
+            }
+        }
+        let remote = self
+            .get_plurks_between(from, to, Some(TimelineFilter::OnlyUser))
+            .await?;
+        Ok(diff_plurks(local, remote))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ArchiveWriter;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    fn write_archive(path: &Path) -> Result<(), PlurkError> {
+        let mut archive = ArchiveWriter::create(path)?;
+
+        let mut staged = archive.stage()?;
+        staged.write_json_line(&serde_json::json!({"id": 3, "nick_name": "alvin"}))?;
+        archive.commit(PROFILE_PATH, staged)?;
+
+        let mut staged = archive.stage()?;
+        for plurk_id in [1, 2] {
+            staged.write_json_line(&PlurkPost {
+                plurk_id,
+                owner_id: 3,
+                ..Default::default()
+            })?;
+        }
+        archive.commit(PLURKS_PATH, staged)?;
+
+        let mut staged = archive.stage()?;
+        staged.write_json_line(&PlurkResponse {
+            id: 7,
+            plurk_id: 1,
+            ..Default::default()
+        })?;
+        archive.commit(RESPONSES_PATH, staged)?;
+
+        let mut staged = archive.stage()?;
+        staged.write_all(b"\x89PNG").map_err(io_error)?;
+        archive.commit("media/a.png", staged)?;
+
+        archive.finish(3)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_archive() -> Result<(), PlurkError> {
+        let tmp_dir = TempDir::new("test_archive").map_err(io_error)?;
+        let path = tmp_dir.path().join("export.tar");
+        write_archive(&path)?;
+
+        let archive = ArchiveReader::open(&path)?;
+        assert_eq!(archive.manifest().user_id, 3);
+        assert_eq!(archive.profile()?.nick_name, "alvin");
+        let ids: Vec<u64> = archive
+            .plurks()?
+            .map(|p| p.map(|p| p.plurk_id))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(ids, [1, 2]);
+        let responses: Vec<PlurkResponse> = archive.responses()?.collect::<Result<_, _>>()?;
+        assert_eq!(responses[0].id, 7);
+        assert!(archive.friends().is_err());
+        assert_eq!(archive.media().collect::<Vec<_>>(), ["media/a.png"]);
+        archive.verify()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_corrupted() -> Result<(), PlurkError> {
+        let tmp_dir = TempDir::new("test_archive").map_err(io_error)?;
+        let path = tmp_dir.path().join("export.tar");
+        write_archive(&path)?;
+
+        let archive = ArchiveReader::open(&path)?;
+        let (offset, _) = archive.entries["media/a.png"];
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .map_err(io_error)?;
+        file.seek(SeekFrom::Start(offset)).map_err(io_error)?;
+        file.write_all(b"X").map_err(io_error)?;
+
+        assert!(archive.verify().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_plurks() {
+        let post = |plurk_id, content_raw: &str| PlurkPost {
+            plurk_id,
+            content_raw: String::from(content_raw),
+            ..Default::default()
+        };
+        let diff = diff_plurks(
+            vec![post(1, "kept"), post(2, "gone"), post(3, "before")],
+            vec![post(1, "kept"), post(3, "after"), post(4, "new")],
+        );
+        assert_eq!(diff.deleted, [post(2, "gone")]);
+        assert_eq!(diff.edited, [(post(3, "before"), post(3, "after"))]);
+        assert!(diff_plurks(vec![post(1, "a")], vec![post(1, "a")]).is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+//! A synchronous wrapper around [`Plurk`](crate::plurk::Plurk) for scripts and
+//! applications that do not run an async runtime.
+
+use crate::plurk::{self, PlurkError};
+use reqwest::Response;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{future::Future, path::Path};
+use tokio::runtime::{self, Runtime};
+
+/// Runs the calls of an async [`plurk::Plurk`] to completion on a private
+/// single-threaded runtime.
+///
+/// Every method of the async API is available through [`Self::run`], e.g.
+/// `plurk.run(|p| p.get_plurk(3))`. Must not be used from within an async runtime.
+#[derive(Debug)]
+pub struct Plurk {
+    inner: plurk::Plurk,
+    runtime: Runtime,
+}
+
+impl Plurk {
+    pub fn new(inner: plurk::Plurk) -> Result<Self, PlurkError> {
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PlurkError::IOError(e.to_string()))?;
+        Ok(Self { inner, runtime })
+    }
+
+    pub fn inner(&self) -> &plurk::Plurk {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> plurk::Plurk {
+        self.inner
+    }
+
+    /// Block on the future returned by `call`.
+    pub fn run<'a, F, Fut>(&'a self, call: F) -> Fut::Output
+    where
+        F: FnOnce(&'a plurk::Plurk) -> Fut,
+        Fut: Future,
+    {
+        self.runtime.block_on(call(&self.inner))
+    }
+
+    /// Like [`Self::run`] for calls needing `&mut`, such as the OAuth flow.
+    pub fn run_mut<'a, F, Fut>(&'a mut self, call: F) -> Fut::Output
+    where
+        F: FnOnce(&'a mut plurk::Plurk) -> Fut,
+        Fut: Future,
+    {
+        self.runtime.block_on(call(&mut self.inner))
+    }
+
+    /// See [`plurk::Plurk::request`].
+    pub fn request<TQuery, TString, TPath>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+        file: Option<(String, TPath)>,
+    ) -> Result<Response, PlurkError>
+    where
+        TQuery: Serialize,
+        TString: Into<String>,
+        TPath: AsRef<Path>,
+    {
+        self.run(|p| p.request(api, query, file))
+    }
+
+    /// See [`plurk::Plurk::request_json`].
+    pub fn request_json<T, TQuery, TString, TPath>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+        file: Option<(String, TPath)>,
+    ) -> Result<T, PlurkError>
+    where
+        T: DeserializeOwned + Send + 'static,
+        TQuery: Serialize,
+        TString: Into<String>,
+        TPath: AsRef<Path>,
+    {
+        self.run(|p| p.request_json(api, query, file))
+    }
+
+    /// Run the PIN based OAuth flow, see [`plurk::Plurk::authorize_interactive`].
+    pub fn authorize_interactive<O, P>(&mut self, open_url: O, get_pin: P) -> Result<(), PlurkError>
+    where
+        O: FnOnce(&str),
+        P: FnOnce() -> String,
+    {
+        self.run_mut(|p| p.authorize_interactive(open_url, async { get_pin() }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocking() {
+        let inner = plurk::Plurk::builder("key", "secret")
+            .base_url("http://127.0.0.1:1")
+            .build()
+            .unwrap();
+        let plurk = Plurk::new(inner).unwrap();
+        assert!(!plurk.run(|p| async { p.is_auth() }));
+        let res = plurk.request_json::<serde_json::Value, _, _, String>(
+            "/APP/echo",
+            Some([("data", "ping")]),
+            None,
+        );
+        assert!(matches!(res, Err(PlurkError::ReqwestError(_))));
+        assert_eq!(
+            plurk.into_inner().to_string(),
+            "Plurk API key (Unauthorized)"
+        );
+    }
+}
@@ -0,0 +1,1021 @@
+use crate::api::timeline::TimelineFilter;
+use crate::api::users::USERS_ME_URL;
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, Utc};
+use futures::{
+    pin_mut,
+    stream::{self, StreamExt, TryStreamExt},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs::{self, File},
+    future::Future,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+use tokio::time::Instant;
+use url::Url;
+
+const FRIENDS_PAGE_LIMIT: usize = 100;
+const MEDIA_URL_PREFIX: &str = "https://images.plurk.com/";
+const MEDIA_CONCURRENCY: usize = 4;
+const MEDIA_HOST_DELAY: Duration = Duration::from_millis(200);
+const MEDIA_RETRIES: u32 = 3;
+/// Delay before the first retry of a download, doubled for every further one.
+const MEDIA_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MANIFEST_VERSION: u32 = 1;
+/// Media commits between two checkpoint saves, see [`ArchiveWriter::commit_media`].
+const MEDIA_CHECKPOINT_EVERY: usize = 100;
+const MEDIA_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+/// Bytes buffered in memory before a staged file is written to, see [`AsyncStagedFile`].
+const WRITE_BUFFER_SIZE: usize = 64 * 1024;
+
+pub const MANIFEST_PATH: &str = "manifest.json";
+pub const PROFILE_PATH: &str = "profile.json";
+pub const PLURKS_PATH: &str = "plurks.jsonl";
+pub const RESPONSES_PATH: &str = "responses.jsonl";
+pub const FRIENDS_PATH: &str = "friends.jsonl";
+pub const MEDIA_DIR: &str = "media";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    /// Hex encoded SHA-256 of the entry.
+    pub sha256: String,
+}
+
+/// A media URL that could not be downloaded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MissingMedia {
+    pub url: String,
+    /// The error of the last attempt.
+    pub error: String,
+}
+
+/// Index of an export archive, stored as its last entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub version: u32,
+    /// RFC 3339 creation time.
+    pub created: String,
+    pub user_id: u64,
+    pub entries: Vec<ManifestEntry>,
+    /// Media that could not be downloaded, even after retrying.
+    #[serde(default)]
+    pub missing_media: Vec<MissingMedia>,
+}
+
+pub(crate) fn io_error(e: io::Error) -> PlurkError {
+    PlurkError::IOError(e.to_string())
+}
+
+/// A file in the staging directory whose SHA-256 and size are computed while writing.
+pub(crate) struct StagedFile {
+    path: PathBuf,
+    file: BufWriter<File>,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl StagedFile {
+    fn create(path: PathBuf) -> Result<Self, PlurkError> {
+        let file = File::create(&path).map_err(io_error)?;
+        Ok(Self {
+            path,
+            file: BufWriter::new(file),
+            hasher: Sha256::new(),
+            size: 0,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn write_json_line<T>(&mut self, value: &T) -> Result<(), PlurkError>
+    where
+        T: Serialize,
+    {
+        serde_json::to_writer(&mut *self, value).map_err(|e| PlurkError::IOError(e.to_string()))?;
+        self.write_all(b"\n").map_err(io_error)
+    }
+
+    fn finish(mut self) -> Result<(PathBuf, u64, String), PlurkError> {
+        self.file.flush().map_err(io_error)?;
+        let digest = self
+            .hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Ok((self.path, self.size, digest))
+    }
+}
+
+impl Write for StagedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.file.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Run blocking file and tar I/O on the blocking thread pool, so writing a large
+/// archive never stalls the workers of the async runtime.
+async fn unblock<T, F>(f: F) -> Result<T, PlurkError>
+where
+    F: FnOnce() -> Result<T, PlurkError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| PlurkError::IOError(e.to_string()))?
+}
+
+/// A [`StagedFile`] written from async code: writes collect in a buffer of at most
+/// about [`WRITE_BUFFER_SIZE`] bytes, which is written out with [`unblock`].
+struct AsyncStagedFile {
+    file: StagedFile,
+    buf: Vec<u8>,
+}
+
+impl AsyncStagedFile {
+    fn new(file: StagedFile) -> Self {
+        Self {
+            file,
+            buf: Vec::new(),
+        }
+    }
+
+    async fn create(path: PathBuf) -> Result<Self, PlurkError> {
+        unblock(move || StagedFile::create(path))
+            .await
+            .map(Self::new)
+    }
+
+    async fn write_all(mut self, data: &[u8]) -> Result<Self, PlurkError> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() < WRITE_BUFFER_SIZE {
+            return Ok(self);
+        }
+        self.flush().await.map(Self::new)
+    }
+
+    async fn write_json_line<T>(self, value: &T) -> Result<Self, PlurkError>
+    where
+        T: Serialize,
+    {
+        let mut line = serde_json::to_vec(value).map_err(|e| PlurkError::IOError(e.to_string()))?;
+        line.push(b'\n');
+        self.write_all(&line).await
+    }
+
+    /// Write out the buffer and return the staged file.
+    async fn flush(self) -> Result<StagedFile, PlurkError> {
+        let Self { mut file, buf } = self;
+        unblock(move || {
+            file.write_all(&buf).map_err(io_error)?;
+            Ok(file)
+        })
+        .await
+    }
+}
+
+/// Progress of an export, saved next to the archive as entries are committed so an
+/// interrupted export can continue where it stopped.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct Checkpoint {
+    /// Archive length after the last committed entry.
+    archive_len: u64,
+    entries: Vec<ManifestEntry>,
+    pub(crate) user_id: Option<u64>,
+    /// Plurks with responses, known once the plurks are committed.
+    pub(crate) with_responses: Option<Vec<u64>>,
+    pub(crate) media: BTreeSet<String>,
+    /// Downloaded again when resuming.
+    pub(crate) missing_media: Vec<MissingMedia>,
+}
+
+/// Path of the checkpoint kept while `path` is being written.
+fn checkpoint_path(path: &Path) -> PathBuf {
+    let mut checkpoint = path.as_os_str().to_owned();
+    checkpoint.push(".checkpoint");
+    PathBuf::from(checkpoint)
+}
+
+/// Appends staged files to the tar archive one at a time, so only the entry being
+/// written ever exists outside the archive.
+pub(crate) struct ArchiveWriter {
+    builder: tar::Builder<File>,
+    staging: PathBuf,
+    staged: usize,
+    checkpoint_path: PathBuf,
+    pub(crate) checkpoint: Checkpoint,
+    /// Paths of `checkpoint.entries`, for constant time lookups.
+    names: HashSet<String>,
+    /// Media commits since the checkpoint was last saved, and when that was.
+    unsaved: usize,
+    saved_at: Instant,
+}
+
+impl ArchiveWriter {
+    pub(crate) fn create(path: &Path) -> Result<Self, PlurkError> {
+        let file = File::create(path).map_err(io_error)?;
+        Self::with_file(path, file, Checkpoint::default())
+    }
+
+    /// Continue the archive at `path` from its checkpoint, dropping anything written after
+    /// the last committed entry. Starts a new archive when there is no checkpoint.
+    pub(crate) fn resume(path: &Path) -> Result<Self, PlurkError> {
+        let raw = match fs::read(checkpoint_path(path)) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::create(path),
+            Err(e) => return Err(io_error(e)),
+        };
+        let checkpoint: Checkpoint =
+            serde_json::from_slice(&raw).map_err(|e| PlurkError::IOError(e.to_string()))?;
+        let mut file = File::options().write(true).open(path).map_err(io_error)?;
+        file.set_len(checkpoint.archive_len).map_err(io_error)?;
+        file.seek(SeekFrom::End(0)).map_err(io_error)?;
+        Self::with_file(path, file, checkpoint)
+    }
+
+    fn with_file(path: &Path, file: File, checkpoint: Checkpoint) -> Result<Self, PlurkError> {
+        let mut staging = path.as_os_str().to_owned();
+        staging.push(".staging");
+        let staging = PathBuf::from(staging);
+        fs::create_dir_all(&staging).map_err(io_error)?;
+        let names = checkpoint.entries.iter().map(|e| e.path.clone()).collect();
+        Ok(Self {
+            builder: tar::Builder::new(file),
+            staging,
+            staged: 0,
+            checkpoint_path: checkpoint_path(path),
+            checkpoint,
+            names,
+            unsaved: 0,
+            saved_at: Instant::now(),
+        })
+    }
+
+    /// Whether `name` was committed, possibly before resuming.
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    pub(crate) fn stage(&mut self) -> Result<StagedFile, PlurkError> {
+        StagedFile::create(self.staging_path())
+    }
+
+    /// A fresh path in the staging directory, for files created outside [`Self::stage`].
+    fn staging_path(&mut self) -> PathBuf {
+        self.staged += 1;
+        self.staging.join(format!("entry-{}", self.staged))
+    }
+
+    pub(crate) fn commit(&mut self, name: &str, staged: StagedFile) -> Result<(), PlurkError> {
+        self.append(name, staged)?;
+        self.save_checkpoint()
+    }
+
+    /// Like [`Self::commit`], saving the checkpoint only every
+    /// [`MEDIA_CHECKPOINT_EVERY`] entries or [`MEDIA_CHECKPOINT_INTERVAL`]. Entries
+    /// appended after the last save are dropped and fetched again when resuming.
+    fn commit_media(&mut self, name: &str, staged: StagedFile) -> Result<(), PlurkError> {
+        self.append(name, staged)?;
+        self.unsaved += 1;
+        if self.unsaved >= MEDIA_CHECKPOINT_EVERY
+            || self.saved_at.elapsed() >= MEDIA_CHECKPOINT_INTERVAL
+        {
+            self.save_checkpoint()?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, name: &str, staged: StagedFile) -> Result<(), PlurkError> {
+        let (path, size, sha256) = staged.finish()?;
+        self.builder
+            .append_path_with_name(&path, name)
+            .map_err(io_error)?;
+        fs::remove_file(&path).map_err(io_error)?;
+        self.checkpoint.entries.push(ManifestEntry {
+            path: name.to_string(),
+            size,
+            sha256,
+        });
+        self.names.insert(name.to_string());
+        Ok(())
+    }
+
+    /// Persist the checkpoint through a temporary file so it is never half written.
+    pub(crate) fn save_checkpoint(&mut self) -> Result<(), PlurkError> {
+        self.checkpoint.archive_len = self.builder.get_mut().stream_position().map_err(io_error)?;
+        let raw =
+            serde_json::to_vec(&self.checkpoint).map_err(|e| PlurkError::IOError(e.to_string()))?;
+        let mut tmp = self.checkpoint_path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, raw).map_err(io_error)?;
+        fs::rename(&tmp, &self.checkpoint_path).map_err(io_error)?;
+        self.unsaved = 0;
+        self.saved_at = Instant::now();
+        Ok(())
+    }
+
+    /// Append the manifest and end the archive. The writer must not be used afterwards.
+    pub(crate) fn finish(&mut self, user_id: u64) -> Result<Manifest, PlurkError> {
+        let checkpoint = std::mem::take(&mut self.checkpoint);
+        let manifest = Manifest {
+            version: MANIFEST_VERSION,
+            created: Utc::now().to_rfc3339(),
+            user_id,
+            entries: checkpoint.entries,
+            missing_media: checkpoint.missing_media,
+        };
+        let data =
+            serde_json::to_vec_pretty(&manifest).map_err(|e| PlurkError::IOError(e.to_string()))?;
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(Utc::now().timestamp().max(0) as u64);
+        self.builder
+            .append_data(&mut header, MANIFEST_PATH, data.as_slice())
+            .map_err(io_error)?;
+        self.builder.finish().map_err(io_error)?;
+        fs::remove_dir_all(&self.staging).map_err(io_error)?;
+        match fs::remove_file(&self.checkpoint_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(io_error(e)),
+            _ => {}
+        }
+        Ok(manifest)
+    }
+}
+
+/// An [`ArchiveWriter`] used from async code, running its I/O with [`unblock`].
+#[derive(Clone)]
+struct BlockingArchive(Arc<Mutex<ArchiveWriter>>);
+
+impl BlockingArchive {
+    async fn open(path: &Path, resume: bool) -> Result<Self, PlurkError> {
+        let path = path.to_path_buf();
+        let archive = unblock(move || {
+            if resume {
+                ArchiveWriter::resume(&path)
+            } else {
+                ArchiveWriter::create(&path)
+            }
+        })
+        .await?;
+        Ok(Self(Arc::new(Mutex::new(archive))))
+    }
+
+    /// The writer, for in-memory state such as the checkpoint. Must not be used for I/O.
+    fn lock(&self) -> MutexGuard<'_, ArchiveWriter> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn run<T, F>(&self, f: F) -> Result<T, PlurkError>
+    where
+        F: FnOnce(&mut ArchiveWriter) -> Result<T, PlurkError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let archive = self.clone();
+        unblock(move || f(&mut archive.lock())).await
+    }
+
+    async fn stage(&self) -> Result<AsyncStagedFile, PlurkError> {
+        self.run(ArchiveWriter::stage)
+            .await
+            .map(AsyncStagedFile::new)
+    }
+
+    async fn commit(&self, name: &'static str, staged: AsyncStagedFile) -> Result<(), PlurkError> {
+        let staged = staged.flush().await?;
+        self.run(move |archive| archive.commit(name, staged)).await
+    }
+
+    async fn finish(&self, user_id: u64) -> Result<Manifest, PlurkError> {
+        self.run(move |archive| archive.finish(user_id)).await
+    }
+}
+
+/// Spaces out requests to the same host by at least `delay`.
+struct HostThrottle {
+    delay: Duration,
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostThrottle {
+    fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn wait(&self, url: &str) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .unwrap_or_default();
+        let slot = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = next.get(&host).map_or(now, |&at| at.max(now));
+            next.insert(host, slot + self.delay);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// Whether a failed download may succeed when tried again: connection errors, timeouts,
+/// rate limiting and server errors.
+fn is_transient(e: &PlurkError) -> bool {
+    let transient_status = |status: reqwest::StatusCode| {
+        status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+    };
+    match e {
+        // `Option::is_none_or` would need Rust 1.82.
+        #[allow(clippy::unnecessary_map_or)]
+        PlurkError::ReqwestError(e) => e.status().map_or(true, transient_status),
+        PlurkError::Api { code, .. } => {
+            reqwest::StatusCode::from_u16(*code).is_ok_and(transient_status)
+        }
+        _ => false,
+    }
+}
+
+/// Run `attempt` until it succeeds, fails with an error `transient` rejects, or
+/// `retries` retries are used up, waiting `delay` before the first retry and twice as
+/// long before each further one.
+async fn retry<T, F, Fut, R>(
+    retries: u32,
+    mut delay: Duration,
+    transient: R,
+    mut attempt: F,
+) -> Result<T, PlurkError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, PlurkError>>,
+    R: Fn(&PlurkError) -> bool,
+{
+    let mut tries = 0;
+    loop {
+        match attempt().await {
+            Err(e) if tries < retries && transient(&e) => {
+                tries += 1;
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Plurk image URLs found in `content`.
+fn media_urls(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .match_indices(MEDIA_URL_PREFIX)
+        .map(move |(start, _)| {
+            let rest = &content[start..];
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "\"'<>()".contains(c))
+                .unwrap_or(rest.len());
+            &rest[..end]
+        })
+}
+
+/// Archive path of a downloaded media URL. The sanitized name is prefixed with a short
+/// hash of the URL, since different URLs can sanitize to the same name.
+fn media_path(url: &str) -> String {
+    let hash: String = Sha256::digest(url.as_bytes())[..4]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let name: String = url
+        .trim_start_matches(MEDIA_URL_PREFIX)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}/{}-{}", MEDIA_DIR, hash, name)
+}
+
+/// Builder for an account export archive, created by [`Plurk::export`].
+///
+/// The archive is a tar file containing the profile, the user's own plurks and their
+/// responses as JSON lines, the friends list, optionally the images referenced in
+/// plurks and responses, and a [`Manifest`] with checksums of every entry.
+///
+/// Entries are streamed to disk as they are fetched and progress is checkpointed after
+/// each one, so an interrupted export resumes from the last committed entry.
+/// Disk writes go through bounded buffers and run on Tokio's blocking thread pool.
+#[derive(Debug, Clone)]
+pub struct ExportBuilder<'a> {
+    plurk: &'a Plurk,
+    media: bool,
+    resume: bool,
+    concurrency: usize,
+    host_delay: Duration,
+    retries: u32,
+}
+
+impl<'a> ExportBuilder<'a> {
+    /// Download images referenced in plurks and responses. Enabled by default.
+    pub fn media(mut self, media: bool) -> Self {
+        self.media = media;
+        self
+    }
+
+    /// Maximum number of images downloaded at once. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Minimum delay between two downloads from the same host. Defaults to 200ms.
+    pub fn host_delay(mut self, host_delay: Duration) -> Self {
+        self.host_delay = host_delay;
+        self
+    }
+
+    /// Times a failed image download is retried, waiting one second before the first
+    /// retry and doubling the delay for each further one. Defaults to 3.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Continue an interrupted export of the same path. Enabled by default; when
+    /// disabled the archive is always written from scratch.
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub async fn write_to<P>(self, path: P) -> Result<Manifest, PlurkError>
+    where
+        P: AsRef<Path>,
+    {
+        let archive = BlockingArchive::open(path.as_ref(), self.resume).await?;
+
+        let user_id = archive.lock().checkpoint.user_id;
+        let user_id = match user_id {
+            Some(user_id) => user_id,
+            None => {
+                let profile: serde_json::Value = self.plurk.call(USERS_ME_URL, None::<()>).await?;
+                let user_id =
+                    profile["id"]
+                        .as_u64()
+                        .ok_or(PlurkError::APICallError(String::from(
+                            "Missing user id in profile",
+                        )))?;
+                let raw = serde_json::to_vec_pretty(&profile)
+                    .map_err(|e| PlurkError::IOError(e.to_string()))?;
+                let staged = archive.stage().await?.write_all(&raw).await?;
+                archive.lock().checkpoint.user_id = Some(user_id);
+                archive.commit(PROFILE_PATH, staged).await?;
+                user_id
+            }
+        };
+
+        if !archive.lock().contains(PLURKS_PATH) {
+            let mut with_responses = Vec::new();
+            let mut staged = archive.stage().await?;
+            let pages = self.plurk.timeline_range_pages(
+                DateTime::<Utc>::MIN_UTC,
+                Utc::now(),
+                Some(TimelineFilter::OnlyUser),
+            );
+            pin_mut!(pages);
+            while let Some(page) = pages.try_next().await? {
+                for plurk in page {
+                    staged = staged.write_json_line(&plurk).await?;
+                    archive
+                        .lock()
+                        .checkpoint
+                        .media
+                        .extend(media_urls(&plurk.content).map(String::from));
+                    if plurk.response_count > 0 {
+                        with_responses.push(plurk.plurk_id);
+                    }
+                }
+            }
+            archive.lock().checkpoint.with_responses = Some(with_responses);
+            archive.commit(PLURKS_PATH, staged).await?;
+        }
+
+        if !archive.lock().contains(RESPONSES_PATH) {
+            let mut staged = archive.stage().await?;
+            let with_responses = archive
+                .lock()
+                .checkpoint
+                .with_responses
+                .clone()
+                .unwrap_or_default();
+            for plurk_id in with_responses {
+                let responses = self.plurk.responses_stream(plurk_id);
+                pin_mut!(responses);
+                while let Some(response) = responses.try_next().await? {
+                    staged = staged.write_json_line(&response).await?;
+                    archive
+                        .lock()
+                        .checkpoint
+                        .media
+                        .extend(media_urls(&response.content).map(String::from));
+                }
+            }
+            archive.commit(RESPONSES_PATH, staged).await?;
+        }
+
+        if !archive.lock().contains(FRIENDS_PATH) {
+            let mut staged = archive.stage().await?;
+            let mut offset = 0;
+            loop {
+                let friends = self
+                    .plurk
+                    .get_friends(user_id, offset, FRIENDS_PAGE_LIMIT)
+                    .await?;
+                for friend in &friends {
+                    staged = staged.write_json_line(friend).await?;
+                }
+                if friends.len() < FRIENDS_PAGE_LIMIT {
+                    break;
+                }
+                offset += friends.len();
+            }
+            archive.commit(FRIENDS_PATH, staged).await?;
+        }
+
+        if self.media {
+            let mut jobs = Vec::new();
+            {
+                let mut archive = archive.lock();
+                // Media missing from an earlier attempt is downloaded again.
+                archive.checkpoint.missing_media.clear();
+                for url in archive.checkpoint.media.clone() {
+                    let name = media_path(&url);
+                    if !archive.contains(&name) {
+                        jobs.push((url, name, archive.staging_path()));
+                    }
+                }
+            }
+
+            let throttle = HostThrottle::new(self.host_delay);
+            let downloads = stream::iter(jobs)
+                .map(|(url, name, staged_path)| {
+                    let throttle = &throttle;
+                    let plurk = self.plurk;
+                    async move {
+                        let res = retry(self.retries, MEDIA_RETRY_DELAY, is_transient, || async {
+                            throttle.wait(&url).await;
+                            let staged = AsyncStagedFile::create(staged_path.clone()).await?;
+                            download(plurk, &url, staged).await?.flush().await
+                        })
+                        .await;
+                        (url, name, res)
+                    }
+                })
+                .buffer_unordered(self.concurrency);
+            pin_mut!(downloads);
+            while let Some((url, name, res)) = downloads.next().await {
+                match res {
+                    Ok(staged) => {
+                        archive
+                            .run(move |archive| archive.commit_media(&name, staged))
+                            .await?
+                    }
+                    Err(e) => archive.lock().checkpoint.missing_media.push(MissingMedia {
+                        url,
+                        error: e.to_string(),
+                    }),
+                }
+            }
+            archive.run(ArchiveWriter::save_checkpoint).await?;
+        }
+
+        archive.finish(user_id).await
+    }
+}
+
+async fn download(
+    plurk: &Plurk,
+    url: &str,
+    mut out: AsyncStagedFile,
+) -> Result<AsyncStagedFile, PlurkError> {
+    let mut res = plurk.get_unsigned(url).await?;
+    while let Some(chunk) = res.chunk().await.map_err(PlurkError::ReqwestError)? {
+        out = out.write_all(&chunk).await?;
+    }
+    Ok(out)
+}
+
+impl Plurk {
+    pub fn export(&self) -> ExportBuilder<'_> {
+        ExportBuilder {
+            plurk: self,
+            media: true,
+            resume: true,
+            concurrency: MEDIA_CONCURRENCY,
+            host_delay: MEDIA_HOST_DELAY,
+            retries: MEDIA_RETRIES,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_media_urls() {
+        let content = r#"look <a href="https://images.plurk.com/abc.jpg" class="pictureservices">
+            <img src="https://images.plurk.com/mx_abc.jpg"></a> and https://images.plurk.com/x/y.png"#;
+        let urls: Vec<&str> = media_urls(content).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://images.plurk.com/abc.jpg",
+                "https://images.plurk.com/mx_abc.jpg",
+                "https://images.plurk.com/x/y.png",
+            ]
+        );
+        let path = media_path(urls[2]);
+        assert!(path.starts_with("media/") && path.ends_with("-x_y.png"));
+        assert_eq!(path.len(), "media/".len() + 9 + "x_y.png".len());
+        assert_ne!(media_path("https://images.plurk.com/x_y.png"), path);
+    }
+
+    #[test]
+    fn test_archive_writer() -> Result<(), PlurkError> {
+        let tmp_dir = TempDir::new("test_export").map_err(io_error)?;
+        let path = tmp_dir.path().join("export.tar");
+
+        let mut archive = ArchiveWriter::create(&path)?;
+        let mut staged = archive.stage()?;
+        staged.write_json_line(&serde_json::json!({"a": 1}))?;
+        archive.commit(PLURKS_PATH, staged)?;
+        let manifest = archive.finish(3)?;
+
+        assert_eq!(manifest.user_id, 3);
+        assert_eq!(
+            manifest.entries,
+            [ManifestEntry {
+                path: String::from(PLURKS_PATH),
+                size: 8,
+                // sha256 of "{\"a\":1}\n"
+                sha256: String::from(
+                    "e346432021b04179518d9614f3560ccd71354a4ee101ddcb893d6959a9d6301c"
+                ),
+            }]
+        );
+        assert!(!tmp_dir.path().join("export.tar.staging").exists());
+        assert!(!tmp_dir.path().join("export.tar.checkpoint").exists());
+
+        let mut tar = tar::Archive::new(File::open(&path).map_err(io_error)?);
+        let mut names = Vec::new();
+        for entry in tar.entries().map_err(io_error)? {
+            let mut entry = entry.map_err(io_error)?;
+            let mut body = String::new();
+            entry.read_to_string(&mut body).map_err(io_error)?;
+            names.push(entry.path().map_err(io_error)?.display().to_string());
+        }
+        assert_eq!(names, [PLURKS_PATH, MANIFEST_PATH]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_writer_resume() -> Result<(), PlurkError> {
+        let tmp_dir = TempDir::new("test_export").map_err(io_error)?;
+        let path = tmp_dir.path().join("export.tar");
+
+        let mut archive = ArchiveWriter::create(&path)?;
+        let mut staged = archive.stage()?;
+        staged.write_json_line(&serde_json::json!({"a": 1}))?;
+        archive.checkpoint.user_id = Some(3);
+        archive.commit(PROFILE_PATH, staged)?;
+        // Interrupted while writing the next entry.
+        let mut staged = archive.stage()?;
+        staged.write_json_line(&serde_json::json!({"b": 2}))?;
+        drop(archive);
+
+        let mut archive = ArchiveWriter::resume(&path)?;
+        assert_eq!(archive.checkpoint.user_id, Some(3));
+        assert!(archive.contains(PROFILE_PATH));
+        assert!(!archive.contains(PLURKS_PATH));
+        let mut staged = archive.stage()?;
+        staged.write_json_line(&serde_json::json!({"b": 2}))?;
+        archive.commit(PLURKS_PATH, staged)?;
+        let manifest = archive.finish(3)?;
+        assert_eq!(manifest.entries.len(), 2);
+
+        let mut tar = tar::Archive::new(File::open(&path).map_err(io_error)?);
+        let mut names = Vec::new();
+        for entry in tar.entries().map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            names.push(entry.path().map_err(io_error)?.display().to_string());
+        }
+        assert_eq!(names, [PROFILE_PATH, PLURKS_PATH, MANIFEST_PATH]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_media_defers_checkpoint() -> Result<(), PlurkError> {
+        let tmp_dir = TempDir::new("test_export").map_err(io_error)?;
+        let path = tmp_dir.path().join("export.tar");
+
+        let mut archive = ArchiveWriter::create(&path)?;
+        let staged = archive.stage()?;
+        archive.commit(PROFILE_PATH, staged)?;
+        let staged = archive.stage()?;
+        archive.commit_media("media/a.png", staged)?;
+        assert!(archive.contains("media/a.png"));
+        drop(archive);
+
+        // Not checkpointed yet, so it is fetched again after resuming.
+        let archive = ArchiveWriter::resume(&path)?;
+        assert!(archive.contains(PROFILE_PATH));
+        assert!(!archive.contains("media/a.png"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_archive() -> Result<(), PlurkError> {
+        let tmp_dir = TempDir::new("test_export").map_err(io_error)?;
+        let path = tmp_dir.path().join("export.tar");
+
+        let archive = BlockingArchive::open(&path, true).await?;
+        let mut staged = archive.stage().await?;
+        // Spills the write buffer to disk several times.
+        let line = serde_json::json!({"content": "a".repeat(1000)});
+        for _ in 0..200 {
+            staged = staged.write_json_line(&line).await?;
+        }
+        archive.commit(PLURKS_PATH, staged).await?;
+        assert!(archive.lock().contains(PLURKS_PATH));
+        let manifest = archive.finish(3).await?;
+
+        let size = 200 * (serde_json::to_vec(&line).unwrap().len() as u64 + 1);
+        assert_eq!(manifest.entries[0].size, size);
+        let mut tar = tar::Archive::new(File::open(&path).map_err(io_error)?);
+        let mut entries = tar.entries().map_err(io_error)?;
+        let entry = entries.next().unwrap().map_err(io_error)?;
+        assert_eq!(entry.header().size().map_err(io_error)?, size);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_host_throttle() {
+        let throttle = HostThrottle::new(Duration::from_millis(50));
+        let start = Instant::now();
+        throttle.wait("https://images.plurk.com/a.jpg").await;
+        throttle.wait("https://example.com/b.jpg").await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        throttle.wait("https://images.plurk.com/c.jpg").await;
+        assert_eq!(start.elapsed(), Duration::from_millis(50));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry() {
+        let start = Instant::now();
+        let mut tries = 0;
+        let res = retry(
+            3,
+            Duration::from_secs(1),
+            |_| true,
+            || {
+                tries += 1;
+                let res = if tries < 3 {
+                    Err(PlurkError::IOError(String::from("timeout")))
+                } else {
+                    Ok(tries)
+                };
+                async move { res }
+            },
+        )
+        .await;
+        assert_eq!(res.unwrap(), 3);
+        // 1s before the second try, 2s before the third.
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+
+        let mut tries = 0;
+        let res: Result<(), _> = retry(
+            3,
+            Duration::from_secs(1),
+            |_| false,
+            || {
+                tries += 1;
+                async { Err(PlurkError::IOError(String::from("not found"))) }
+            },
+        )
+        .await;
+        assert!(res.is_err());
+        assert_eq!(tries, 1);
+
+        let mut tries = 0;
+        let res: Result<(), _> = retry(
+            2,
+            Duration::from_secs(1),
+            |_| true,
+            || {
+                tries += 1;
+                async { Err(PlurkError::IOError(String::from("timeout"))) }
+            },
+        )
+        .await;
+        assert_eq!(res.unwrap_err().to_string(), "IO Error: timeout");
+        assert_eq!(tries, 3);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test(start_paused = true)]
+    async fn test_download_retry() -> Result<(), PlurkError> {
+        use crate::testing::MockServer;
+
+        let tmp_dir = TempDir::new("test_export").map_err(io_error)?;
+        let fetch = |plurk: Plurk, url: &'static str| {
+            let path = tmp_dir.path().join("a.jpg");
+            retry(2, Duration::from_secs(1), is_transient, move || {
+                let (plurk, path) = (plurk.clone(), path.clone());
+                async move {
+                    let staged = AsyncStagedFile::create(path).await?;
+                    download(&plurk, url, staged).await?.flush().await
+                }
+            })
+        };
+
+        let server = MockServer::new().mock_status("/a.jpg", 503, "busy");
+        let start = Instant::now();
+        let res = fetch(server.plurk(), "https://images.plurk.com/a.jpg").await;
+        assert!(matches!(res, Err(PlurkError::Api { code: 503, .. })));
+        // 1s before the second try, 2s before the third.
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.path == "/a.jpg" && !r.signed));
+
+        let server = MockServer::new();
+        let res = fetch(server.plurk(), "https://images.plurk.com/a.jpg").await;
+        assert!(matches!(res, Err(PlurkError::Api { code: 404, .. })));
+        assert_eq!(server.requests().len(), 1);
+
+        let server = MockServer::new().mock("/a.jpg", "jpeg");
+        let staged = fetch(server.plurk(), "https://images.plurk.com/a.jpg").await?;
+        assert_eq!(staged.size, 4);
+        Ok(())
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test(start_paused = true)]
+    async fn test_export_media_through_transport() -> Result<(), PlurkError> {
+        use crate::api::friends_fans::FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL;
+        use crate::api::timeline::TIMELINE_GET_PLURKS_URL;
+        use crate::testing::{fixtures, MockServer};
+
+        let plurks = r#"{"plurks": [{
+            "plurk_id": 1, "owner_id": 3, "qualifier": "shares",
+            "content": "look https://images.plurk.com/a.jpg",
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT"
+        }]}"#;
+        let server = MockServer::new()
+            .mock(USERS_ME_URL, fixtures::USERS_ME)
+            .mock(TIMELINE_GET_PLURKS_URL, plurks)
+            .mock(FRIENDS_FANS_GET_FRIENDS_BY_OFFSET_URL, "[]")
+            .mock_status("/a.jpg", 503, "busy");
+        let tmp_dir = TempDir::new("test_export").map_err(io_error)?;
+        let manifest = server
+            .plurk()
+            .export()
+            .retries(1)
+            .write_to(tmp_dir.path().join("export.tar"))
+            .await?;
+
+        assert_eq!(manifest.missing_media.len(), 1);
+        assert_eq!(
+            manifest.missing_media[0].url,
+            "https://images.plurk.com/a.jpg"
+        );
+        let media: Vec<_> = server
+            .requests()
+            .into_iter()
+            .filter(|r| r.path == "/a.jpg")
+            .collect();
+        assert_eq!(media.len(), 2);
+        Ok(())
+    }
+}
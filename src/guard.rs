@@ -0,0 +1,191 @@
+use crate::plurk::{Plurk, PlurkError};
+use std::{
+    collections::hash_map::DefaultHasher,
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+type WarnFn = dyn Fn(&str) + Send + Sync;
+
+/// Client-side anti-flood guard remembering the content posted within a time window.
+///
+/// Plurk flags accounts that repeatedly submit the same content, so once installed with
+/// [`Plurk::with_duplicate_guard`] plurks and responses repeating recent content are
+/// rejected before reaching the API, or only reported when [`Self::warn_only`] is set.
+/// Clones share the same history.
+#[derive(Clone)]
+pub struct DuplicateGuard {
+    window: Duration,
+    warn: Option<Arc<WarnFn>>,
+    recent: Arc<Mutex<VecDeque<(Instant, u64)>>>,
+}
+
+impl DuplicateGuard {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            warn: None,
+            recent: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Let duplicates through, calling `warn` with the repeated content instead.
+    pub fn warn_only<F>(mut self, warn: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.warn = Some(Arc::new(warn));
+        self
+    }
+
+    fn key(plurk_id: Option<u64>, content: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        plurk_id.hash(&mut hasher);
+        content.trim().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Reserve `key` unless it was posted or reserved within the window, so concurrent
+    /// identical submissions cannot both pass.
+    fn reserve_at(&self, key: u64, content: &str, now: Instant) -> Result<(), PlurkError> {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        while recent
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= self.window)
+        {
+            recent.pop_front();
+        }
+        if recent.iter().any(|(_, k)| *k == key) {
+            match &self.warn {
+                Some(warn) => warn(content),
+                None => {
+                    return Err(PlurkError::InvalidArgument(format!(
+                        "Duplicate content posted within {:?}",
+                        self.window
+                    )))
+                }
+            }
+        }
+        recent.push_back((now, key));
+        Ok(())
+    }
+
+    /// Drop the latest reservation of `key`, used when the post failed.
+    fn release(&self, key: u64) {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pos) = recent.iter().rposition(|(_, k)| *k == key) {
+            recent.remove(pos);
+        }
+    }
+}
+
+impl fmt::Debug for DuplicateGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DuplicateGuard")
+            .field("window", &self.window)
+            .field("warn_only", &self.warn.is_some())
+            .finish()
+    }
+}
+
+impl Plurk {
+    pub fn with_duplicate_guard(mut self, guard: DuplicateGuard) -> Self {
+        self.guard = Some(guard);
+        self
+    }
+
+    /// Run `post` unless the guard rejects `content`. The content is reserved before
+    /// posting and forgotten again if the post fails. `plurk_id` scopes responses to the
+    /// plurk they are posted on.
+    pub(crate) async fn guarded<T, F>(
+        &self,
+        plurk_id: Option<u64>,
+        content: &str,
+        post: F,
+    ) -> Result<T, PlurkError>
+    where
+        F: std::future::Future<Output = Result<T, PlurkError>>,
+    {
+        let Some(guard) = &self.guard else {
+            return post.await;
+        };
+        let key = DuplicateGuard::key(plurk_id, content);
+        guard.reserve_at(key, content, Instant::now())?;
+        let res = post.await;
+        if res.is_err() {
+            guard.release(key);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_duplicate() {
+        let guard = DuplicateGuard::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let key = DuplicateGuard::key(None, "hello");
+        assert_eq!(key, DuplicateGuard::key(None, " hello\n"));
+        assert_ne!(key, DuplicateGuard::key(Some(1), "hello"));
+
+        assert!(guard.reserve_at(key, "hello", start).is_ok());
+        assert!(guard.reserve_at(key, "hello", start).is_err());
+        assert!(guard
+            .reserve_at(key, "hello", start + Duration::from_secs(60))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_warn_only() {
+        let warned = Arc::new(Mutex::new(Vec::new()));
+        let sink = warned.clone();
+        let guard = DuplicateGuard::new(Duration::from_secs(60))
+            .warn_only(move |content| sink.lock().unwrap().push(content.to_string()));
+        let now = Instant::now();
+        let key = DuplicateGuard::key(None, "hello");
+
+        assert!(guard.reserve_at(key, "hello", now).is_ok());
+        assert!(guard.reserve_at(key, "hello", now).is_ok());
+        assert_eq!(*warned.lock().unwrap(), ["hello"]);
+    }
+
+    fn guarded_plurk() -> Plurk {
+        Plurk::new("key", "secret", None, None)
+            .with_duplicate_guard(DuplicateGuard::new(Duration::from_secs(60)))
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates() {
+        let plurk = guarded_plurk();
+        let post = || async {
+            tokio::task::yield_now().await;
+            Ok::<_, PlurkError>(())
+        };
+        let (first, second) = tokio::join!(
+            plurk.guarded(None, "hello", post()),
+            plurk.guarded(None, "hello", post()),
+        );
+        assert_eq!([first.is_ok(), second.is_ok()], [true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_release_on_error() {
+        let plurk = guarded_plurk();
+        let failed = plurk
+            .guarded(None, "hello", async {
+                Err::<(), _>(PlurkError::InvalidArgument("offline".into()))
+            })
+            .await;
+        assert!(failed.is_err());
+        assert!(plurk
+            .guarded(None, "hello", async { Ok::<_, PlurkError>(()) })
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::api::timeline::TimelineFilter;
+use crate::models::{PlurkPost, PlurkResponse, Qualifier};
+use crate::plurk::{Plurk, PlurkError};
+use chrono::{DateTime, Duration, Utc};
+use futures::{pin_mut, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum LedgerEntry {
+    /// Sent, outcome unknown. `started` is RFC 3339.
+    Pending {
+        started: String,
+    },
+    Plurk {
+        plurk: PlurkPost,
+    },
+    Response {
+        response: PlurkResponse,
+    },
+}
+
+/// Persistent record of write operations keyed by caller-supplied idempotency keys.
+///
+/// A key is recorded as pending before the request is sent and replaced by the created
+/// plurk or response once it succeeds. Retrying a key that already succeeded returns the
+/// recorded result; retrying a pending key, e.g. after a timeout, first looks for the
+/// content on Plurk so an attempt that went through is not posted twice.
+#[derive(Debug)]
+pub struct IdempotencyLedger {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, LedgerEntry>>,
+}
+
+fn io_error<E: ToString>(e: E) -> PlurkError {
+    PlurkError::IOError(e.to_string())
+}
+
+/// Whether `content` posted at `posted` may come from an attempt started at `started`.
+/// Plurk only keeps second precision, so allow one second of slack.
+fn is_attempt(
+    content_raw: &str,
+    posted: &DateTime<Utc>,
+    content: &str,
+    started: &DateTime<Utc>,
+) -> bool {
+    content_raw.trim() == content.trim() && *posted >= *started - Duration::seconds(1)
+}
+
+impl IdempotencyLedger {
+    /// Open the ledger stored at `path`, starting empty when the file does not exist.
+    pub fn open<P>(path: P) -> Result<Self, PlurkError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let entries = match fs::read(&path) {
+            Ok(raw) => serde_json::from_slice(&raw).map_err(io_error)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(io_error(e)),
+        };
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    fn get(&self, key: &str) -> Option<LedgerEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(key).cloned()
+    }
+
+    /// Record `entry` and write the ledger through a temporary file, so a crash never
+    /// leaves it half written.
+    fn set(&self, key: &str, entry: LedgerEntry) -> Result<(), PlurkError> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(key.to_string(), entry);
+        let raw = serde_json::to_vec_pretty(&*entries).map_err(io_error)?;
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, raw).map_err(io_error)?;
+        fs::rename(&tmp, &self.path).map_err(io_error)
+    }
+
+    fn start(&self, key: &str) -> Result<(), PlurkError> {
+        let started = Utc::now().to_rfc3339();
+        self.set(key, LedgerEntry::Pending { started })
+    }
+}
+
+fn parse_started(started: &str) -> Result<DateTime<Utc>, PlurkError> {
+    DateTime::parse_from_rfc3339(started)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(io_error)
+}
+
+impl Plurk {
+    /// Post a plurk at most once for `key`, see [`IdempotencyLedger`].
+    pub async fn add_plurk_once(
+        &self,
+        ledger: &IdempotencyLedger,
+        key: &str,
+        content: &str,
+        qualifier: Qualifier,
+    ) -> Result<PlurkPost, PlurkError> {
+        match ledger.get(key) {
+            Some(LedgerEntry::Plurk { plurk }) => return Ok(plurk),
+            Some(LedgerEntry::Response { .. }) => {
+                return Err(PlurkError::InvalidArgument(format!(
+                    "Idempotency key {} was used for a response",
+                    key
+                )))
+            }
+            Some(LedgerEntry::Pending { started }) => {
+                let started = parse_started(&started)?;
+                let posted = self
+                    .get_plurks_between(
+                        started - Duration::seconds(1),
+                        Utc::now(),
+                        Some(TimelineFilter::OnlyUser),
+                    )
+                    .await?
+                    .into_iter()
+                    .find(|p| is_attempt(&p.content_raw, &p.posted, content, &started));
+                if let Some(plurk) = posted {
+                    ledger.set(
+                        key,
+                        LedgerEntry::Plurk {
+                            plurk: plurk.clone(),
+                        },
+                    )?;
+                    return Ok(plurk);
+                }
+            }
+            None => {}
+        }
+
+        ledger.start(key)?;
+        let plurk = self.add_plurk(content, qualifier).await?;
+        ledger.set(
+            key,
+            LedgerEntry::Plurk {
+                plurk: plurk.clone(),
+            },
+        )?;
+        Ok(plurk)
+    }
+
+    /// Respond to `plurk_id` at most once for `key`, see [`IdempotencyLedger`].
+    pub async fn add_response_once(
+        &self,
+        ledger: &IdempotencyLedger,
+        key: &str,
+        plurk_id: u64,
+        content: &str,
+        qualifier: Qualifier,
+    ) -> Result<PlurkResponse, PlurkError> {
+        match ledger.get(key) {
+            Some(LedgerEntry::Response { response }) => return Ok(response),
+            Some(LedgerEntry::Plurk { .. }) => {
+                return Err(PlurkError::InvalidArgument(format!(
+                    "Idempotency key {} was used for a plurk",
+                    key
+                )))
+            }
+            Some(LedgerEntry::Pending { started }) => {
+                let started = parse_started(&started)?;
+                let responses = self.responses_stream(plurk_id);
+                pin_mut!(responses);
+                let mut posted = None;
+                while let Some(response) = responses.try_next().await? {
+                    if is_attempt(&response.content_raw, &response.posted, content, &started) {
+                        posted = Some(response);
+                        break;
+                    }
+                }
+                if let Some(response) = posted {
+                    ledger.set(
+                        key,
+                        LedgerEntry::Response {
+                            response: response.clone(),
+                        },
+                    )?;
+                    return Ok(response);
+                }
+            }
+            None => {}
+        }
+
+        ledger.start(key)?;
+        let response = self.respond(plurk_id, content, qualifier).await?;
+        ledger.set(
+            key,
+            LedgerEntry::Response {
+                response: response.clone(),
+            },
+        )?;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_ledger_persists() -> Result<(), PlurkError> {
+        let tmp_dir = TempDir::new("test_ledger").map_err(io_error)?;
+        let path = tmp_dir.path().join("ledger.json");
+
+        let ledger = IdempotencyLedger::open(&path)?;
+        assert_eq!(ledger.get("a"), None);
+        ledger.start("a")?;
+        let plurk = PlurkPost {
+            plurk_id: 3,
+            ..Default::default()
+        };
+        ledger.set("b", LedgerEntry::Plurk { plurk })?;
+
+        let ledger = IdempotencyLedger::open(&path)?;
+        assert!(matches!(ledger.get("a"), Some(LedgerEntry::Pending { .. })));
+        assert!(matches!(
+            ledger.get("b"),
+            Some(LedgerEntry::Plurk { plurk }) if plurk.plurk_id == 3
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_attempt() {
+        let started = Utc.with_ymd_and_hms(2023, 5, 1, 9, 0, 0).unwrap();
+        let at = |s| Utc.with_ymd_and_hms(2023, 5, 1, 9, 0, s).unwrap();
+        assert!(is_attempt("hello ", &at(5), "hello", &started));
+        assert!(is_attempt(
+            "hello",
+            &(started - Duration::seconds(1)),
+            "hello",
+            &started
+        ));
+        assert!(!is_attempt(
+            "hello",
+            &(started - Duration::seconds(2)),
+            "hello",
+            &started
+        ));
+        assert!(!is_attempt("hello!", &at(5), "hello", &started));
+    }
+}
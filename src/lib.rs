@@ -1,3 +1,33 @@
+#[cfg(feature = "client")]
+pub mod api;
+#[cfg(feature = "export")]
+pub mod archive;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "client")]
+pub mod guard;
+#[cfg(feature = "client")]
+pub mod ledger;
+#[cfg(feature = "client")]
+pub mod middleware;
+pub mod models;
+#[cfg(feature = "client")]
+pub mod oauth1;
+#[cfg(feature = "client")]
+pub mod outbox;
+#[cfg(feature = "client")]
 pub mod plurk;
+#[cfg(feature = "client")]
+pub mod rate_limit;
+#[cfg(feature = "client")]
+pub mod runtime;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+#[cfg(feature = "client")]
 pub mod secret;
-pub mod oauth1;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "client")]
+pub mod transport;
@@ -0,0 +1,142 @@
+use crate::plurk::{Plurk, PlurkError};
+use reqwest::{Method, Request, Response, Url};
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Hooks run around every request sent by [`Plurk`], registered with
+/// [`Plurk::with_middleware`], e.g. for logging, metrics or extra headers.
+///
+/// Middleware run in the order they were registered.
+pub trait PlurkMiddleware: Send + Sync {
+    /// Called with the signed request right before it is sent. Headers may be added
+    /// freely; changing the URL or body invalidates the OAuth signature. An error aborts
+    /// the request.
+    fn before_send(&self, _request: &mut Request) -> Result<(), PlurkError> {
+        Ok(())
+    }
+
+    /// Called once the response headers arrived or sending failed, with the time taken.
+    fn after_receive(
+        &self,
+        _method: &Method,
+        _url: &Url,
+        _result: Result<&Response, &PlurkError>,
+        _elapsed: Duration,
+    ) {
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Middlewares(Vec<Arc<dyn PlurkMiddleware>>);
+
+impl Middlewares {
+    pub(crate) fn before_send(&self, request: &mut Request) -> Result<(), PlurkError> {
+        self.0.iter().try_for_each(|m| m.before_send(request))
+    }
+
+    pub(crate) fn after_receive(
+        &self,
+        method: &Method,
+        url: &Url,
+        result: Result<&Response, &PlurkError>,
+        elapsed: Duration,
+    ) {
+        for m in &self.0 {
+            m.after_receive(method, url, result, elapsed);
+        }
+    }
+}
+
+impl fmt::Debug for Middlewares {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} middleware", self.0.len())
+    }
+}
+
+impl Plurk {
+    pub fn with_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: PlurkMiddleware + 'static,
+    {
+        self.middlewares.0.push(Arc::new(middleware));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        seen: Arc<Mutex<Vec<String>>>,
+        reject: bool,
+    }
+
+    impl PlurkMiddleware for Recorder {
+        fn before_send(&self, request: &mut Request) -> Result<(), PlurkError> {
+            request
+                .headers_mut()
+                .insert("x-app", "test".parse().unwrap());
+            self.seen.lock().unwrap().push(format!(
+                "before {} {}",
+                request.url().path(),
+                request
+                    .headers()
+                    .contains_key(reqwest::header::AUTHORIZATION)
+            ));
+            if self.reject {
+                return Err(PlurkError::InvalidArgument(String::from("rejected")));
+            }
+            Ok(())
+        }
+
+        fn after_receive(
+            &self,
+            _method: &Method,
+            url: &Url,
+            result: Result<&Response, &PlurkError>,
+            _elapsed: Duration,
+        ) {
+            self.seen
+                .lock()
+                .unwrap()
+                .push(format!("after {} {}", url.path(), result.is_ok()));
+        }
+    }
+
+    fn plurk() -> Plurk {
+        // Nothing listens on port 1, so sending fails right away.
+        Plurk::builder("key", "secret")
+            .base_url("http://127.0.0.1:1")
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_middleware_hooks() {
+        let recorder = Recorder::default();
+        let seen = recorder.seen.clone();
+        let plurk = plurk().with_middleware(recorder);
+
+        assert!(plurk.get("/APP/echo", None::<()>).await.is_err());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["before /APP/echo true", "after /APP/echo false"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middleware_rejects() {
+        let recorder = Recorder {
+            reject: true,
+            ..Default::default()
+        };
+        let seen = recorder.seen.clone();
+        let plurk = plurk().with_middleware(recorder);
+
+        let res = plurk.get("/APP/echo", None::<()>).await;
+        assert!(matches!(res, Err(PlurkError::InvalidArgument(_))));
+        assert_eq!(*seen.lock().unwrap(), ["before /APP/echo true"]);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::models::UnknownVariant;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Who may respond to a plurk, the `no_comments` value of Plurk.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum CommentPermission {
+    #[default]
+    Enabled,
+    Disabled,
+    OnlyFriends,
+}
+
+impl From<CommentPermission> for u8 {
+    fn from(permission: CommentPermission) -> Self {
+        match permission {
+            CommentPermission::Enabled => 0,
+            CommentPermission::Disabled => 1,
+            CommentPermission::OnlyFriends => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for CommentPermission {
+    type Error = UnknownVariant;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Enabled),
+            1 => Ok(Self::Disabled),
+            2 => Ok(Self::OnlyFriends),
+            _ => Err(UnknownVariant::new("comment permission", value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for CommentPermission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", u8::from(*self))
+    }
+}
+
+impl FromStr for CommentPermission {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<u8>()
+            .map_err(|_| UnknownVariant::new("comment permission", s))?
+            .try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_permission() {
+        assert_eq!(CommentPermission::OnlyFriends.to_string(), "2");
+        assert_eq!(
+            "1".parse::<CommentPermission>().unwrap(),
+            CommentPermission::Disabled
+        );
+        assert!("3".parse::<CommentPermission>().is_err());
+        assert!("no".parse::<CommentPermission>().is_err());
+        assert_eq!(
+            serde_json::to_string(&CommentPermission::Disabled).unwrap(),
+            "1"
+        );
+        let permission: CommentPermission = serde_json::from_str("2").unwrap();
+        assert_eq!(permission, CommentPermission::OnlyFriends);
+        assert!(serde_json::from_str::<CommentPermission>("7").is_err());
+    }
+}
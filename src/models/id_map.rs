@@ -0,0 +1,62 @@
+//! `#[serde(deserialize_with = "id_map::deserialize")]` for maps keyed by id, such as
+//! the `plurk_users` and `friends` maps of timeline and response payloads.
+//!
+//! Plurk sends the ids as string keys, which `serde_json` already converts into `u64`,
+//! but answers `[]` instead of `{}` when the map is empty.
+
+use serde::{de::Error, Deserialize, Deserializer};
+use std::collections::HashMap;
+
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<HashMap<u64, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum IdMap<T> {
+        // Keys are parsed by hand: buffered untagged content cannot deserialize string
+        // keys as numbers.
+        Map(HashMap<String, T>),
+        Empty([(); 0]),
+    }
+
+    match Option::<IdMap<T>>::deserialize(deserializer)? {
+        Some(IdMap::Map(map)) => map
+            .into_iter()
+            .map(|(id, value)| match id.parse() {
+                Ok(id) => Ok((id, value)),
+                Err(_) => Err(D::Error::custom(format!("invalid id: {}", id))),
+            })
+            .collect(),
+        Some(IdMap::Empty(_)) | None => Ok(HashMap::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::PlurkUser;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize)]
+    struct Page {
+        #[serde(default, deserialize_with = "super::deserialize")]
+        users: HashMap<u64, PlurkUser>,
+    }
+
+    #[test]
+    fn test_id_map() {
+        let raw = r#"{"users": {"3": {"id": 3, "nick_name": "alvin"}}}"#;
+        let page: Page = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.users[&3].nick_name, "alvin");
+
+        for raw in [r#"{"users": []}"#, r#"{"users": null}"#, "{}"] {
+            let page: Page = serde_json::from_str(raw).unwrap();
+            assert!(page.users.is_empty());
+        }
+
+        assert!(serde_json::from_str::<Page>(r#"{"users": {"alvin": {}}}"#).is_err());
+        assert!(serde_json::from_str::<Page>(r#"{"users": [3]}"#).is_err());
+    }
+}
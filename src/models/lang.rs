@@ -0,0 +1,192 @@
+use crate::models::UnknownVariant;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// Language codes accepted by Plurk.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlurkLang {
+    English,
+    Portuguese,
+    SimplifiedChinese,
+    Catalan,
+    Greek,
+    Danish,
+    German,
+    Spanish,
+    Swedish,
+    NorwegianBokmal,
+    Hindi,
+    Romanian,
+    Croatian,
+    French,
+    Russian,
+    Italian,
+    Japanese,
+    Hebrew,
+    Hungarian,
+    Dutch,
+    Thai,
+    Filipino,
+    Indonesian,
+    Polish,
+    Arabic,
+    Finnish,
+    TraditionalChinese,
+    Turkish,
+    Irish,
+    Slovak,
+    Ukrainian,
+    Persian,
+    /// A code this crate does not know yet, as sent by Plurk.
+    Other(String),
+}
+
+impl PlurkLang {
+    /// Every language accepted by Plurk.
+    pub const ALL: [Self; 32] = [
+        Self::English,
+        Self::Portuguese,
+        Self::SimplifiedChinese,
+        Self::Catalan,
+        Self::Greek,
+        Self::Danish,
+        Self::German,
+        Self::Spanish,
+        Self::Swedish,
+        Self::NorwegianBokmal,
+        Self::Hindi,
+        Self::Romanian,
+        Self::Croatian,
+        Self::French,
+        Self::Russian,
+        Self::Italian,
+        Self::Japanese,
+        Self::Hebrew,
+        Self::Hungarian,
+        Self::Dutch,
+        Self::Thai,
+        Self::Filipino,
+        Self::Indonesian,
+        Self::Polish,
+        Self::Arabic,
+        Self::Finnish,
+        Self::TraditionalChinese,
+        Self::Turkish,
+        Self::Irish,
+        Self::Slovak,
+        Self::Ukrainian,
+        Self::Persian,
+    ];
+
+    pub fn code(&self) -> &str {
+        match self {
+            Self::English => "en",
+            Self::Portuguese => "pt_BR",
+            Self::SimplifiedChinese => "cn",
+            Self::Catalan => "ca",
+            Self::Greek => "el",
+            Self::Danish => "dk",
+            Self::German => "de",
+            Self::Spanish => "es",
+            Self::Swedish => "sv",
+            Self::NorwegianBokmal => "nb",
+            Self::Hindi => "hi",
+            Self::Romanian => "ro",
+            Self::Croatian => "hr",
+            Self::French => "fr",
+            Self::Russian => "ru",
+            Self::Italian => "it",
+            Self::Japanese => "ja",
+            Self::Hebrew => "he",
+            Self::Hungarian => "hu",
+            Self::Dutch => "ne",
+            Self::Thai => "th",
+            Self::Filipino => "ta_fp",
+            Self::Indonesian => "in",
+            Self::Polish => "pl",
+            Self::Arabic => "ar",
+            Self::Finnish => "fi",
+            Self::TraditionalChinese => "tr_ch",
+            Self::Turkish => "tr",
+            Self::Irish => "ga",
+            Self::Slovak => "sk",
+            Self::Ukrainian => "uk",
+            Self::Persian => "fa",
+            Self::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for PlurkLang {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl Serialize for PlurkLang {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.code())
+    }
+}
+
+/// Unknown codes become [`PlurkLang::Other`] rather than failing the whole payload.
+impl<'de> Deserialize<'de> for PlurkLang {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Ok(raw.parse().unwrap_or(Self::Other(raw)))
+    }
+}
+
+/// Only known codes parse; use [`PlurkLang::Other`] to send any other one.
+impl FromStr for PlurkLang {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.code() == s)
+            .ok_or_else(|| UnknownVariant::new("language", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lang_code() {
+        assert_eq!(PlurkLang::TraditionalChinese.to_string(), "tr_ch");
+        assert_eq!(
+            serde_json::to_string(&PlurkLang::Portuguese).unwrap(),
+            "\"pt_BR\""
+        );
+        let lang: PlurkLang = serde_json::from_str("\"ja\"").unwrap();
+        assert_eq!(lang, PlurkLang::Japanese);
+        let lang: PlurkLang = serde_json::from_str("\"eo\"").unwrap();
+        assert_eq!(lang, PlurkLang::Other(String::from("eo")));
+        assert_eq!(serde_json::to_string(&lang).unwrap(), "\"eo\"");
+    }
+
+    #[test]
+    fn test_parse_lang() {
+        assert_eq!(
+            "tr_ch".parse::<PlurkLang>().unwrap(),
+            PlurkLang::TraditionalChinese
+        );
+        assert_eq!(
+            "jp".parse::<PlurkLang>().unwrap_err().to_string(),
+            "Unknown language: jp"
+        );
+        for lang in PlurkLang::ALL {
+            assert_eq!(lang.code().parse::<PlurkLang>().unwrap(), lang);
+            let json = serde_json::to_string(&lang).unwrap();
+            assert_eq!(serde_json::from_str::<PlurkLang>(&json).unwrap(), lang);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use crate::models::UnknownVariant;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// Audience of a plurk.
+///
+/// Plurk reports it as `"|3||5|"`, with `"|0|"` meaning all friends and the owner's own
+/// id alone meaning only the owner, and takes a JSON list of ids when posting.
+/// [`Self::Private`] and [`Self::Cliques`] are resolved to ids when posting; serialized
+/// on their own they read `"|me|"` and `"|clique:Family|"`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LimitedTo {
+    #[default]
+    Public,
+    /// Only the owner.
+    Private,
+    /// All friends of the owner.
+    Friends,
+    Users(Vec<u64>),
+    /// The members of the owner's cliques with these names.
+    Cliques(Vec<String>),
+}
+
+const PRIVATE: &str = "me";
+const CLIQUE_PREFIX: &str = "clique:";
+
+impl LimitedTo {
+    pub fn is_public(&self) -> bool {
+        *self == Self::Public
+    }
+
+    /// `Users` holding only `owner_id` becomes [`Self::Private`].
+    pub(crate) fn for_owner(self, owner_id: u64) -> Self {
+        match self {
+            Self::Users(ids) if ids == [owner_id] => Self::Private,
+            limited_to => limited_to,
+        }
+    }
+
+    fn parts(&self) -> Vec<String> {
+        match self {
+            Self::Public => Vec::new(),
+            Self::Private => vec![PRIVATE.to_string()],
+            Self::Friends => vec![String::from("0")],
+            Self::Users(ids) => ids.iter().map(u64::to_string).collect(),
+            Self::Cliques(names) => names
+                .iter()
+                .map(|name| format!("{}{}", CLIQUE_PREFIX, name))
+                .collect(),
+        }
+    }
+}
+
+impl From<&[u64]> for LimitedTo {
+    fn from(ids: &[u64]) -> Self {
+        Self::Users(ids.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[u64; N]> for LimitedTo {
+    fn from(ids: &[u64; N]) -> Self {
+        Self::Users(ids.to_vec())
+    }
+}
+
+impl From<Vec<u64>> for LimitedTo {
+    fn from(ids: Vec<u64>) -> Self {
+        Self::Users(ids)
+    }
+}
+
+impl fmt::Display for LimitedTo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for part in self.parts() {
+            write!(f, "|{}|", part)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LimitedTo {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('|').filter(|part| !part.is_empty()).collect();
+        let invalid = || UnknownVariant::new("limited_to", s);
+        match parts.as_slice() {
+            [] => Ok(Self::Public),
+            [PRIVATE] => Ok(Self::Private),
+            ["0"] => Ok(Self::Friends),
+            [first, ..] if first.starts_with(CLIQUE_PREFIX) => parts
+                .iter()
+                .map(|part| part.strip_prefix(CLIQUE_PREFIX).map(String::from))
+                .collect::<Option<Vec<String>>>()
+                .map(Self::Cliques)
+                .ok_or_else(invalid),
+            _ => parts
+                .iter()
+                .map(|id| id.parse().map_err(|_| invalid()))
+                .collect::<Result<Vec<u64>, _>>()
+                .map(Self::Users),
+        }
+    }
+}
+
+impl Serialize for LimitedTo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Public => serializer.serialize_none(),
+            _ => serializer.serialize_some(&self.to_string()),
+        }
+    }
+}
+
+/// Accepts Plurk's `"|3||5|"` and `null`.
+impl<'de> Deserialize<'de> for LimitedTo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(raw) => raw.parse().map_err(serde::de::Error::custom),
+            None => Ok(Self::Public),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limited_to() {
+        assert_eq!("".parse::<LimitedTo>().unwrap(), LimitedTo::Public);
+        assert_eq!("|0|".parse::<LimitedTo>().unwrap(), LimitedTo::Friends);
+        assert_eq!(
+            "|3||5|".parse::<LimitedTo>().unwrap(),
+            LimitedTo::Users(vec![3, 5])
+        );
+        assert_eq!("|me|".parse::<LimitedTo>().unwrap(), LimitedTo::Private);
+        assert_eq!(
+            "|clique:Family||clique:Work|".parse::<LimitedTo>().unwrap(),
+            LimitedTo::Cliques(vec![String::from("Family"), String::from("Work")])
+        );
+        assert!("|you|".parse::<LimitedTo>().is_err());
+        assert!("|clique:Family||3|".parse::<LimitedTo>().is_err());
+        assert_eq!(LimitedTo::Users(vec![3, 5]).to_string(), "|3||5|");
+        assert_eq!(LimitedTo::Friends.to_string(), "|0|");
+        assert_eq!(LimitedTo::Users(vec![3]).for_owner(3), LimitedTo::Private);
+        assert_eq!(
+            LimitedTo::Users(vec![3, 5]).for_owner(3),
+            LimitedTo::Users(vec![3, 5])
+        );
+        assert!(LimitedTo::default().is_public());
+    }
+
+    #[test]
+    fn test_serde_limited_to() {
+        let users = LimitedTo::from(&[3, 5]);
+        assert_eq!(serde_json::to_string(&users).unwrap(), r#""|3||5|""#);
+        assert_eq!(serde_json::to_string(&LimitedTo::Public).unwrap(), "null");
+        let parsed: LimitedTo = serde_json::from_str(r#""|3||5|""#).unwrap();
+        assert_eq!(parsed, users);
+        assert!(serde_json::from_str::<LimitedTo>("[0]").is_err());
+        for limited_to in [
+            LimitedTo::Private,
+            LimitedTo::Friends,
+            LimitedTo::Cliques(vec![String::from("Family")]),
+        ] {
+            let json = serde_json::to_string(&limited_to).unwrap();
+            assert_eq!(
+                serde_json::from_str::<LimitedTo>(&json).unwrap(),
+                limited_to
+            );
+        }
+        let parsed: LimitedTo = serde_json::from_str("null").unwrap();
+        assert_eq!(parsed, LimitedTo::Public);
+    }
+}
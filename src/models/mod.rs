@@ -0,0 +1,82 @@
+mod comment_permission;
+pub mod id_map;
+mod lang;
+mod limited_to;
+mod plurk_id;
+mod post;
+mod privacy;
+mod qualifier;
+mod response;
+mod text;
+pub mod time;
+mod user;
+
+pub use comment_permission::CommentPermission;
+pub use lang::PlurkLang;
+pub use limited_to::LimitedTo;
+pub use plurk_id::PlurkId;
+pub use post::PlurkPost;
+pub use privacy::Privacy;
+pub use qualifier::Qualifier;
+pub use response::PlurkResponse;
+pub use user::{AvatarSize, PlurkUser};
+
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// The `owner_id` / `user_id` Plurk reports for anonymous plurks and their responses.
+pub const ANONYMOUS_USER_ID: u64 = 99999;
+
+/// Response fields not modelled by this crate, kept as raw JSON.
+pub type Extra = serde_json::Map<String, serde_json::Value>;
+
+/// Access to the fields a model did not recognise, e.g. ones Plurk added after this
+/// crate was released.
+pub trait Extension {
+    fn extra(&self) -> &Extra;
+
+    /// Deserialize the unrecognised fields into a user defined type.
+    fn extra_as<E>(&self) -> Result<E, serde_json::Error>
+    where
+        E: DeserializeOwned,
+    {
+        serde_json::from_value(serde_json::Value::Object(self.extra().clone()))
+    }
+}
+
+macro_rules! impl_extension {
+    ($($model:ty),*) => {
+        $(impl Extension for $model {
+            fn extra(&self) -> &Extra {
+                &self.extra
+            }
+        })*
+    };
+}
+
+impl_extension!(PlurkPost, PlurkResponse, PlurkUser);
+
+/// Returned when parsing a string into one of the model enums fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownVariant {
+    kind: &'static str,
+    value: String,
+}
+
+impl UnknownVariant {
+    pub(crate) fn new<S>(kind: &'static str, value: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            kind,
+            value: value.into(),
+        }
+    }
+}
+
+impl fmt::Display for UnknownVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown {}: {}", self.kind, self.value)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::models::UnknownVariant;
+use std::{fmt, str::FromStr};
+
+const PLURK_URL_PREFIX: &str = "https://www.plurk.com/p/";
+const URL_PREFIXES: [&str; 4] = [
+    PLURK_URL_PREFIX,
+    "https://plurk.com/p/",
+    "http://www.plurk.com/p/",
+    "http://plurk.com/p/",
+];
+
+fn strip_url_prefix(raw: &str) -> Option<&str> {
+    URL_PREFIXES
+        .iter()
+        .find_map(|prefix| raw.strip_prefix(prefix))
+}
+
+fn parse_base36(id: &str, raw: &str) -> Result<PlurkId, UnknownVariant> {
+    let id = id.trim_end_matches('/');
+    let id = id.split(['?', '#']).next().unwrap_or_default();
+    if id.is_empty() {
+        return Err(UnknownVariant::new("plurk id", raw));
+    }
+    u64::from_str_radix(&id.to_lowercase(), 36)
+        .map(PlurkId)
+        .map_err(|_| UnknownVariant::new("plurk id", raw))
+}
+
+/// Id of a plurk. Displays and parses as the numeric id the API expects.
+///
+/// Plurk URLs carry the id in base 36, see [`Self::from_url`] and [`Self::from_base36`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PlurkId(pub u64);
+
+impl PlurkId {
+    /// The id as it appears in plurk URLs.
+    pub fn base36(&self) -> String {
+        let mut id = self.0;
+        let mut digits = Vec::new();
+        loop {
+            digits.push(std::char::from_digit((id % 36) as u32, 36).unwrap_or('0'));
+            id /= 36;
+            if id == 0 {
+                break;
+            }
+        }
+        digits.iter().rev().collect()
+    }
+
+    /// Parse the base 36 id of a plurk URL, e.g. `oqj7vc`.
+    pub fn from_base36(id: &str) -> Result<Self, UnknownVariant> {
+        parse_base36(id.trim(), id)
+    }
+
+    /// Parse a plurk URL such as `https://www.plurk.com/p/oqj7vc`.
+    pub fn from_url(url: &str) -> Result<Self, UnknownVariant> {
+        let id =
+            strip_url_prefix(url.trim()).ok_or_else(|| UnknownVariant::new("plurk url", url))?;
+        parse_base36(id, url)
+    }
+
+    pub fn to_url(&self) -> String {
+        format!("{}{}", PLURK_URL_PREFIX, self.base36())
+    }
+}
+
+impl From<PlurkId> for u64 {
+    fn from(id: PlurkId) -> Self {
+        id.0
+    }
+}
+
+impl From<u64> for PlurkId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for PlurkId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for PlurkId {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .parse()
+            .map(Self)
+            .map_err(|_| UnknownVariant::new("plurk id", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plurk_id() {
+        assert_eq!(
+            PlurkId::from_base36("oqj7vc").unwrap(),
+            PlurkId(1_495_754_904)
+        );
+        assert_eq!(
+            PlurkId::from_url("https://www.plurk.com/p/oqj7vc/").unwrap(),
+            PlurkId(1_495_754_904)
+        );
+        assert_eq!(PlurkId(1_495_754_904).base36(), "oqj7vc");
+        assert_eq!(PlurkId(0).base36(), "0");
+        assert_eq!(PlurkId(35).to_url(), "https://www.plurk.com/p/z");
+        assert_eq!(u64::from(PlurkId(35)), 35);
+        assert_eq!(PlurkId::from(3).to_string(), "3");
+        assert!("".parse::<PlurkId>().is_err());
+        assert!(PlurkId::from_base36("").is_err());
+        assert!(PlurkId::from_url("https://www.plurk.com/p/not-an-id").is_err());
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(
+            "1495754904".parse::<PlurkId>().unwrap(),
+            PlurkId(1_495_754_904)
+        );
+        for id in [0, 36, 1_495_754_904, u64::MAX] {
+            assert_eq!(
+                PlurkId(id).to_string().parse::<PlurkId>().unwrap(),
+                PlurkId(id)
+            );
+        }
+        assert!("oqj7vc".parse::<PlurkId>().is_err());
+        assert!("https://www.plurk.com/p/oqj7vc".parse::<PlurkId>().is_err());
+    }
+
+    #[test]
+    fn test_from_url() {
+        assert_eq!(
+            PlurkId::from_url("http://plurk.com/p/oqj7vc?r=1").unwrap(),
+            PlurkId(1_495_754_904)
+        );
+        assert_eq!(
+            PlurkId::from_url(&PlurkId(1_495_754_904).to_url()).unwrap(),
+            PlurkId(1_495_754_904)
+        );
+        assert!(PlurkId::from_url("oqj7vc").is_err());
+        assert!(PlurkId::from_url("https://www.plurk.com/p/").is_err());
+    }
+}
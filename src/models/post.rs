@@ -0,0 +1,170 @@
+use crate::models::text::html_to_text;
+use crate::models::time;
+use crate::models::{
+    CommentPermission, Extra, LimitedTo, PlurkId, PlurkLang, Qualifier, ANONYMOUS_USER_ID,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A plurk as returned by the timeline, polling and search endpoints.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(remote = "Self")]
+pub struct PlurkPost {
+    pub plurk_id: u64,
+    pub owner_id: u64,
+    pub content: String,
+    #[serde(default)]
+    pub content_raw: String,
+    pub qualifier: Qualifier,
+    #[serde(with = "time::plurk_date")]
+    pub posted: DateTime<Utc>,
+    #[serde(default)]
+    pub response_count: u64,
+    #[serde(default)]
+    pub is_unread: u8,
+    #[serde(default)]
+    pub lang: Option<PlurkLang>,
+    #[serde(default)]
+    pub anonymous: bool,
+    /// Whether the authorized user liked the plurk.
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub favorite_count: u64,
+    /// Whether the authorized user replurked the plurk.
+    #[serde(default)]
+    pub replurked: bool,
+    #[serde(default)]
+    pub replurkers_count: u64,
+    #[serde(default)]
+    pub replurkable: bool,
+    #[serde(default)]
+    pub no_comments: CommentPermission,
+    #[serde(default)]
+    pub plurk_type: u8,
+    /// Plurk reports plurks only the owner sees as limited to the owner's id; those
+    /// become [`LimitedTo::Private`].
+    #[serde(default)]
+    pub limited_to: LimitedTo,
+    /// Fields not modelled above, see [`Extension`](crate::models::Extension).
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Serialize for PlurkPost {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        PlurkPost::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PlurkPost {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut post = PlurkPost::deserialize(deserializer)?;
+        post.limited_to = std::mem::take(&mut post.limited_to).for_owner(post.owner_id);
+        Ok(post)
+    }
+}
+
+impl PlurkPost {
+    /// Link to the plurk on plurk.com.
+    pub fn url(&self) -> String {
+        PlurkId(self.plurk_id).to_url()
+    }
+
+    /// `content` as plain text, for displaying outside a browser. `content_raw` holds
+    /// the text as the author typed it, markup included.
+    pub fn text(&self) -> String {
+        html_to_text(&self.content)
+    }
+
+    /// Whether the owner is hidden; `owner_id` is then [`ANONYMOUS_USER_ID`].
+    pub fn is_anonymous(&self) -> bool {
+        self.anonymous || self.owner_id == ANONYMOUS_USER_ID
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_deserialize_post() {
+        let raw = r#"{
+            "plurk_id": 3,
+            "qualifier": "is",
+            "is_unread": 1,
+            "plurk_type": 0,
+            "user_id": 3,
+            "owner_id": 3,
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT",
+            "no_comments": 0,
+            "content": "hello",
+            "content_raw": "hello",
+            "response_count": 2
+        }"#;
+        let post: PlurkPost = serde_json::from_str(raw).unwrap();
+        assert_eq!(post.plurk_id, 3);
+        assert_eq!(post.response_count, 2);
+        assert_eq!(
+            post.posted,
+            Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, 13).unwrap()
+        );
+        assert!(!post.is_anonymous());
+        assert_eq!(post.limited_to, LimitedTo::Public);
+        assert_eq!(post.url(), "https://www.plurk.com/p/3");
+        assert!(!post.extra.contains_key("plurk_type"));
+
+        let raw = r#"{
+            "plurk_id": 4, "owner_id": 99999, "content": "psst", "qualifier": "whispers",
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT", "anonymous": true
+        }"#;
+        let post: PlurkPost = serde_json::from_str(raw).unwrap();
+        assert!(post.anonymous && post.is_anonymous());
+        assert_eq!(post.qualifier, Qualifier::Whispers);
+    }
+
+    #[test]
+    fn test_limited_to() {
+        let raw = r#"{
+            "plurk_id": 4, "owner_id": 3, "content": "hi", "qualifier": ":",
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT", "limited_to": "|3||5|", "favorite": true, "favorite_count": 2
+        }"#;
+        let post: PlurkPost = serde_json::from_str(raw).unwrap();
+        assert_eq!(post.limited_to, LimitedTo::Users(vec![3, 5]));
+        assert!(post.favorite);
+        assert_eq!(post.no_comments, CommentPermission::Enabled);
+
+        let json = serde_json::to_string(&post).unwrap();
+        let post: PlurkPost = serde_json::from_str(&json).unwrap();
+        assert_eq!(post.limited_to, LimitedTo::Users(vec![3, 5]));
+
+        let raw = r#"{"plurk_id": 4, "owner_id": 3, "content": "", "qualifier": ":",
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT", "limited_to": null}"#;
+        let post: PlurkPost = serde_json::from_str(raw).unwrap();
+        assert_eq!(post.limited_to, LimitedTo::Public);
+
+        let raw = r#"{"plurk_id": 4, "owner_id": 3, "content": "", "qualifier": ":",
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT", "limited_to": "|3|"}"#;
+        let post: PlurkPost = serde_json::from_str(raw).unwrap();
+        assert_eq!(post.limited_to, LimitedTo::Private);
+        let json = serde_json::to_string(&post).unwrap();
+        let post: PlurkPost = serde_json::from_str(&json).unwrap();
+        assert_eq!(post.limited_to, LimitedTo::Private);
+
+        let mut post = post;
+        post.limited_to = LimitedTo::Cliques(vec![String::from("Family")]);
+        let json = serde_json::to_string(&post).unwrap();
+        let post: PlurkPost = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            post.limited_to,
+            LimitedTo::Cliques(vec![String::from("Family")])
+        );
+    }
+}
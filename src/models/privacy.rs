@@ -0,0 +1,51 @@
+use crate::models::UnknownVariant;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Who can view a user's profile.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Privacy {
+    World,
+    OnlyFriends,
+}
+
+impl fmt::Display for Privacy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::World => write!(f, "world"),
+            Self::OnlyFriends => write!(f, "only_friends"),
+        }
+    }
+}
+
+impl FromStr for Privacy {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "world" => Ok(Self::World),
+            "only_friends" => Ok(Self::OnlyFriends),
+            _ => Err(UnknownVariant::new("privacy", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_privacy() {
+        assert_eq!("world".parse::<Privacy>().unwrap(), Privacy::World);
+        assert_eq!(Privacy::OnlyFriends.to_string(), "only_friends");
+        assert_eq!(
+            serde_json::to_string(&Privacy::OnlyFriends).unwrap(),
+            "\"only_friends\""
+        );
+        assert_eq!(
+            "only_friend".parse::<Privacy>().unwrap_err().to_string(),
+            "Unknown privacy: only_friend"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::models::UnknownVariant;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// The verb shown between the user name and the content of a plurk or response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Qualifier {
+    Loves,
+    Likes,
+    Shares,
+    Gives,
+    Hates,
+    Wants,
+    Has,
+    Will,
+    Asks,
+    Wishes,
+    Was,
+    Feels,
+    Thinks,
+    Says,
+    Is,
+    /// No verb, shown as `:`.
+    #[default]
+    Freestyle,
+    Wonders,
+    Whispers,
+    Hopes,
+    Needs,
+    /// A qualifier this crate does not know yet, as sent by Plurk.
+    Other(String),
+}
+
+impl Qualifier {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Loves => "loves",
+            Self::Likes => "likes",
+            Self::Shares => "shares",
+            Self::Gives => "gives",
+            Self::Hates => "hates",
+            Self::Wants => "wants",
+            Self::Has => "has",
+            Self::Will => "will",
+            Self::Asks => "asks",
+            Self::Wishes => "wishes",
+            Self::Was => "was",
+            Self::Feels => "feels",
+            Self::Thinks => "thinks",
+            Self::Says => "says",
+            Self::Is => "is",
+            Self::Freestyle => ":",
+            Self::Wonders => "wonders",
+            Self::Whispers => "whispers",
+            Self::Hopes => "hopes",
+            Self::Needs => "needs",
+            Self::Other(qualifier) => qualifier,
+        }
+    }
+}
+
+impl fmt::Display for Qualifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<Qualifier> for String {
+    fn from(qualifier: Qualifier) -> Self {
+        qualifier.as_str().to_string()
+    }
+}
+
+impl Serialize for Qualifier {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Unknown qualifiers become [`Qualifier::Other`] rather than failing the whole plurk.
+impl<'de> Deserialize<'de> for Qualifier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Ok(raw.parse().unwrap_or(Self::Other(raw)))
+    }
+}
+
+/// Only known qualifiers parse; use [`Qualifier::Other`] to send any other one.
+impl FromStr for Qualifier {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "loves" => Ok(Self::Loves),
+            "likes" => Ok(Self::Likes),
+            "shares" => Ok(Self::Shares),
+            "gives" => Ok(Self::Gives),
+            "hates" => Ok(Self::Hates),
+            "wants" => Ok(Self::Wants),
+            "has" => Ok(Self::Has),
+            "will" => Ok(Self::Will),
+            "asks" => Ok(Self::Asks),
+            "wishes" => Ok(Self::Wishes),
+            "was" => Ok(Self::Was),
+            "feels" => Ok(Self::Feels),
+            "thinks" => Ok(Self::Thinks),
+            "says" => Ok(Self::Says),
+            "is" => Ok(Self::Is),
+            ":" | "freestyle" => Ok(Self::Freestyle),
+            "wonders" => Ok(Self::Wonders),
+            "whispers" => Ok(Self::Whispers),
+            "hopes" => Ok(Self::Hopes),
+            "needs" => Ok(Self::Needs),
+            _ => Err(UnknownVariant::new("qualifier", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualifier() {
+        assert_eq!("says".parse::<Qualifier>().unwrap(), Qualifier::Says);
+        assert_eq!(":".parse::<Qualifier>().unwrap(), Qualifier::Freestyle);
+        assert_eq!(Qualifier::Freestyle.to_string(), ":");
+        assert_eq!(
+            serde_json::to_string(&Qualifier::Whispers).unwrap(),
+            "\"whispers\""
+        );
+        let qualifier: Qualifier = serde_json::from_str("\":\"").unwrap();
+        assert_eq!(qualifier, Qualifier::Freestyle);
+        let qualifier: Qualifier = serde_json::from_str("\"yells\"").unwrap();
+        assert_eq!(qualifier, Qualifier::Other(String::from("yells")));
+        assert_eq!(serde_json::to_string(&qualifier).unwrap(), "\"yells\"");
+        assert_eq!(
+            "yells".parse::<Qualifier>().unwrap_err().to_string(),
+            "Unknown qualifier: yells"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::models::text::html_to_text;
+use crate::models::time;
+use crate::models::{Extra, PlurkLang, Qualifier, ANONYMOUS_USER_ID};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A single response (comment) under a plurk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PlurkResponse {
+    pub id: u64,
+    pub user_id: u64,
+    pub plurk_id: u64,
+    pub content: String,
+    #[serde(default)]
+    pub content_raw: String,
+    pub qualifier: Qualifier,
+    #[serde(with = "time::plurk_date")]
+    pub posted: DateTime<Utc>,
+    #[serde(default)]
+    pub lang: Option<PlurkLang>,
+    /// Pseudonym shown for the author of a response on an anonymous plurk.
+    #[serde(default)]
+    pub handle: Option<String>,
+    /// Fields not modelled above, see [`Extension`](crate::models::Extension).
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl PlurkResponse {
+    /// `content` as plain text, for displaying outside a browser. `content_raw` holds
+    /// the text as the author typed it, markup included.
+    pub fn text(&self) -> String {
+        html_to_text(&self.content)
+    }
+
+    /// Whether the author is hidden; `user_id` is then [`ANONYMOUS_USER_ID`].
+    pub fn is_anonymous(&self) -> bool {
+        self.user_id == ANONYMOUS_USER_ID
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_response() {
+        let raw = r#"{
+            "id": 1,
+            "user_id": 3,
+            "plurk_id": 5,
+            "content": "<b>hi</b>",
+            "content_raw": "**hi**",
+            "qualifier": "says",
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT",
+            "lang": "en"
+        }"#;
+        let res: PlurkResponse = serde_json::from_str(raw).unwrap();
+        assert_eq!(res.id, 1);
+        assert_eq!(res.plurk_id, 5);
+        assert_eq!(res.content_raw, "**hi**");
+        assert_eq!(res.text(), "hi");
+        assert_eq!(res.qualifier, Qualifier::Says);
+        assert_eq!(res.lang, Some(PlurkLang::English));
+        assert_eq!(
+            time::format_posted(&res.posted),
+            "Fri, 05 Jun 2009 23:07:13 GMT"
+        );
+        assert!(!res.is_anonymous());
+    }
+
+    #[test]
+    fn test_deserialize_anonymous_response() {
+        let raw = r#"{
+            "id": 1, "user_id": 99999, "plurk_id": 5, "content": "hi", "qualifier": ":",
+            "posted": "Fri, 05 Jun 2009 23:07:13 GMT", "handle": "shy-cat"
+        }"#;
+        let res: PlurkResponse = serde_json::from_str(raw).unwrap();
+        assert!(res.is_anonymous());
+        assert_eq!(res.handle.as_deref(), Some("shy-cat"));
+        assert_eq!(res.qualifier, Qualifier::Freestyle);
+    }
+}
@@ -0,0 +1,78 @@
+/// Plain text of the HTML Plurk renders into `content`: tags are dropped, `<br>` becomes
+/// a line break and entities are decoded.
+pub(crate) fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(i) = rest.find(['<', '&']) {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with('<') {
+            let Some(end) = rest.find('>') else {
+                break;
+            };
+            let tag = rest[1..end].trim().to_lowercase();
+            if tag == "br" || tag.starts_with("br ") || tag.starts_with("br/") {
+                text.push('\n');
+            }
+            rest = &rest[end + 1..];
+        } else {
+            match rest.find(';').filter(|&end| end <= 10) {
+                Some(end) => match decode_entity(&rest[1..end]) {
+                    Some(c) => {
+                        text.push(c);
+                        rest = &rest[end + 1..];
+                    }
+                    None => {
+                        text.push('&');
+                        rest = &rest[1..];
+                    }
+                },
+                None => {
+                    text.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text(r#"<b>hi</b> <a href="https://x.com/?a=1&amp;b=2">link</a>"#),
+            "hi link"
+        );
+        assert_eq!(html_to_text("a<br />b<BR>c"), "a\nb\nc");
+        assert_eq!(html_to_text("&lt;3 &#39;x&#x27; &quot;"), "<3 'x' \"");
+        assert_eq!(
+            html_to_text("fish & chips &unknown;"),
+            "fish & chips &unknown;"
+        );
+        assert_eq!(html_to_text("1 < 2"), "1 < 2");
+    }
+}
@@ -0,0 +1,122 @@
+//! Conversions between `chrono` and the date formats of the Plurk API.
+
+use chrono::{DateTime, Utc};
+
+/// Format used by the `offset` parameters, e.g. `2009-6-20T21:55:34`.
+const OFFSET_FORMAT: &str = "%Y-%-m-%-dT%H:%M:%S";
+
+/// Format of the dates Plurk returns, e.g. `Fri, 05 Jun 2009 23:07:13 GMT`.
+const POSTED_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Parse the RFC 2822 style dates Plurk returns, e.g. `Fri, 05 Jun 2009 23:07:13 GMT`.
+pub fn parse_posted(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(raw)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Inverse of [`parse_posted`].
+pub fn format_posted(time: &DateTime<Utc>) -> String {
+    time.format(POSTED_FORMAT).to_string()
+}
+
+/// Format `time` for the `offset` parameter of the timeline and polling endpoints.
+pub fn format_offset(time: &DateTime<Utc>) -> String {
+    time.format(OFFSET_FORMAT).to_string()
+}
+
+/// `#[serde(with = "plurk_date")]` for `DateTime<Utc>` fields holding Plurk dates.
+pub mod plurk_date {
+    use super::{format_posted, parse_posted};
+    use chrono::{DateTime, Utc};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format_posted(time))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        parse_posted(&raw).ok_or_else(|| D::Error::custom(format!("invalid date: {}", raw)))
+    }
+}
+
+/// Like [`plurk_date`] for `Option<DateTime<Utc>>`; `null` and `""` become `None`.
+pub mod plurk_date_option {
+    use super::{format_posted, parse_posted};
+    use chrono::{DateTime, Utc};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(time: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match time {
+            Some(time) => serializer.serialize_some(&format_posted(time)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(raw) if !raw.is_empty() => parse_posted(&raw)
+                .map(Some)
+                .ok_or_else(|| D::Error::custom(format!("invalid date: {}", raw))),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde::{Deserialize, Serialize};
+
+    #[test]
+    fn test_parse_posted() {
+        let t = parse_posted("Fri, 05 Jun 2009 23:07:13 GMT").unwrap();
+        assert_eq!(t, Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, 13).unwrap());
+        assert_eq!(format_posted(&t), "Fri, 05 Jun 2009 23:07:13 GMT");
+        assert_eq!(parse_posted("yesterday"), None);
+    }
+
+    #[test]
+    fn test_format_offset() {
+        let t = Utc.with_ymd_and_hms(2009, 6, 20, 21, 55, 34).unwrap();
+        assert_eq!(format_offset(&t), "2009-6-20T21:55:34");
+    }
+
+    #[test]
+    fn test_serde_helpers() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Dated {
+            #[serde(with = "plurk_date")]
+            posted: DateTime<Utc>,
+            #[serde(default, with = "plurk_date_option")]
+            issued: Option<DateTime<Utc>>,
+        }
+
+        let raw = r#"{"posted":"Fri, 05 Jun 2009 23:07:13 GMT","issued":""}"#;
+        let dated: Dated = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            dated.posted,
+            Utc.with_ymd_and_hms(2009, 6, 5, 23, 7, 13).unwrap()
+        );
+        assert_eq!(dated.issued, None);
+        assert_eq!(
+            serde_json::to_string(&dated).unwrap(),
+            r#"{"posted":"Fri, 05 Jun 2009 23:07:13 GMT","issued":null}"#
+        );
+        assert!(serde_json::from_str::<Dated>(r#"{"posted":"now"}"#).is_err());
+    }
+}
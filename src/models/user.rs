@@ -0,0 +1,201 @@
+use crate::models::time;
+use crate::models::{Extra, PlurkLang};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const AVATAR_HOST: &str = "https://avatars.plk.io";
+const DEFAULT_AVATAR_HOST: &str = "https://www.plurk.com/static";
+
+/// Size of a profile image, see [`PlurkUser::avatar_url`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AvatarSize {
+    /// 20x20 pixels.
+    Small,
+    /// 45x45 pixels.
+    Medium,
+    /// Up to 195x195 pixels.
+    Big,
+}
+
+impl AvatarSize {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Small => "small",
+            Self::Medium => "medium",
+            Self::Big => "big",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Big => "jpg",
+            _ => "gif",
+        }
+    }
+}
+
+/// A Plurk user as embedded in alerts, profiles and timeline user maps.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlurkUser {
+    pub id: u64,
+    pub nick_name: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub karma: Option<f64>,
+    /// Version of the profile image, used to build its URL.
+    #[serde(default)]
+    pub avatar: Option<u64>,
+    #[serde(default)]
+    pub has_profile_image: u8,
+    /// `0` female, `1` male, `2` not stating / other.
+    #[serde(default)]
+    pub gender: Option<u8>,
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Midnight UTC of the birthday, `None` if the user hides it.
+    #[serde(default, with = "time::plurk_date_option")]
+    pub date_of_birth: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub premium: bool,
+    #[serde(default)]
+    pub default_lang: Option<PlurkLang>,
+    /// Fields not modelled above, see [`Extension`](crate::models::Extension).
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl PlurkUser {
+    /// The display name if the user set one, the nick name otherwise.
+    pub fn name(&self) -> &str {
+        match &self.display_name {
+            Some(name) if !name.is_empty() => name,
+            _ => &self.nick_name,
+        }
+    }
+
+    /// URL of the profile image, or of Plurk's default image for users without one.
+    pub fn avatar_url(&self, size: AvatarSize) -> String {
+        if self.has_profile_image == 0 {
+            return format!("{}/default_{}.gif", DEFAULT_AVATAR_HOST, size.name());
+        }
+        let version = self
+            .avatar
+            .filter(|&v| v != 0)
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        format!(
+            "{}/{}-{}{}.{}",
+            AVATAR_HOST,
+            self.id,
+            size.name(),
+            version,
+            size.extension()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Extension;
+    use chrono::{NaiveDate, TimeZone};
+    use serde::Deserialize;
+
+    #[test]
+    fn test_user_name() {
+        let raw = r#"{"id": 3, "nick_name": "alvin", "display_name": "amix", "karma": 33.5}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(user.name(), "amix");
+        assert_eq!(user.karma, Some(33.5));
+
+        let raw = r#"{"id": 3, "nick_name": "alvin", "display_name": ""}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(user.name(), "alvin");
+    }
+
+    #[test]
+    fn test_avatar_url() {
+        let raw = r#"{"id": 3, "nick_name": "alvin", "has_profile_image": 1, "avatar": 7}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            user.avatar_url(AvatarSize::Small),
+            "https://avatars.plk.io/3-small7.gif"
+        );
+        assert_eq!(
+            user.avatar_url(AvatarSize::Big),
+            "https://avatars.plk.io/3-big7.jpg"
+        );
+
+        let raw = r#"{"id": 3, "nick_name": "alvin", "has_profile_image": 1, "avatar": null}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            user.avatar_url(AvatarSize::Medium),
+            "https://avatars.plk.io/3-medium.gif"
+        );
+
+        let raw = r#"{"id": 3, "nick_name": "alvin"}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            user.avatar_url(AvatarSize::Medium),
+            "https://www.plurk.com/static/default_medium.gif"
+        );
+        assert_eq!(
+            user.avatar_url(AvatarSize::Big),
+            "https://www.plurk.com/static/default_big.gif"
+        );
+    }
+
+    #[test]
+    fn test_user_details() {
+        let raw = r#"{
+            "id": 3, "nick_name": "alvin", "gender": 1, "location": "Taipei, Taiwan",
+            "date_of_birth": "Sat, 19 Mar 1983 00:00:00 GMT", "premium": true,
+            "default_lang": "tr_ch"
+        }"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(user.gender, Some(1));
+        assert_eq!(user.location.as_deref(), Some("Taipei, Taiwan"));
+        assert_eq!(
+            user.date_of_birth,
+            Some(Utc.with_ymd_and_hms(1983, 3, 19, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            user.date_of_birth.map(|t| t.date_naive()),
+            NaiveDate::from_ymd_opt(1983, 3, 19)
+        );
+        let json = serde_json::to_value(&user).unwrap();
+        assert_eq!(json["date_of_birth"], "Sat, 19 Mar 1983 00:00:00 GMT");
+        assert!(user.premium);
+        assert_eq!(user.default_lang, Some(PlurkLang::TraditionalChinese));
+
+        let raw = r#"{"id": 3, "nick_name": "alvin", "default_lang": "xx_new"}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            user.default_lang,
+            Some(PlurkLang::Other(String::from("xx_new")))
+        );
+        assert!(user.extra.is_empty());
+
+        let raw = r#"{"id": 3, "nick_name": "alvin", "date_of_birth": null}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(user.date_of_birth, None);
+        assert!(!user.premium);
+    }
+
+    #[test]
+    fn test_extra_fields() {
+        #[derive(Deserialize)]
+        struct Fans {
+            fans_count: u64,
+        }
+
+        let raw = r#"{"id": 3, "nick_name": "alvin", "fans_count": 33}"#;
+        let user: PlurkUser = serde_json::from_str(raw).unwrap();
+        assert_eq!(user.extra.len(), 1);
+        assert_eq!(user.extra_as::<Fans>().unwrap().fans_count, 33);
+        assert_eq!(serde_json::to_value(&user).unwrap()["fans_count"], 33);
+    }
+}
@@ -6,6 +6,45 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 type QueryPair = Vec<(String, String)>;
 
+fn derive_key(secret: &Secret) -> hmac::Key {
+    hmac::Key::new(
+        hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+        secret.get_sign_secret().as_bytes(),
+    )
+}
+
+/// Reusable signer holding the HMAC key derived from a [`Secret`], so the key is only
+/// rebuilt when the token changes instead of on every request.
+#[derive(Clone, Debug)]
+pub struct Signer {
+    secret: Secret,
+    key: hmac::Key,
+}
+
+impl Signer {
+    pub fn new(secret: Secret) -> Self {
+        let key = derive_key(&secret);
+        Self { secret, key }
+    }
+
+    pub fn secret(&self) -> &Secret {
+        &self.secret
+    }
+
+    pub fn update_token<TString>(&mut self, token_key: TString, token_secret: TString)
+    where
+        TString: Into<String>,
+    {
+        self.secret.update_token_mut(token_key, token_secret);
+        self.key = derive_key(&self.secret);
+    }
+
+    /// A fresh [`Oauth1`] (new nonce and timestamp) using the cached key.
+    pub fn oauth(&self) -> Oauth1 {
+        Oauth1::with_key(&self.secret, self.key.clone())
+    }
+}
+
 pub struct Oauth1 {
     oauth_consumer_key: String,
     oauth_token: Option<String>,
@@ -17,11 +56,16 @@ pub struct Oauth1 {
     oauth_callback: Option<String>,
     oauth_verifier: Option<String>,
     realm: Option<String>,
-    sign_key: String,
+    sign_key: hmac::Key,
 }
 
 impl Oauth1 {
     pub fn new(secret: Secret) -> Self {
+        let sign_key = derive_key(&secret);
+        Self::with_key(&secret, sign_key)
+    }
+
+    fn with_key(secret: &Secret, sign_key: hmac::Key) -> Self {
         Self {
             oauth_consumer_key: secret.get_consumer_key(),
             oauth_token: secret.get_token_key(),
@@ -33,7 +77,7 @@ impl Oauth1 {
             oauth_callback: None,
             oauth_verifier: None,
             realm: None,
-            sign_key: secret.get_sign_secret(),
+            sign_key,
         }
     }
 
@@ -61,7 +105,7 @@ impl Oauth1 {
     }
 
     pub fn to_header(&self) -> String {
-        let mut res = format!("OAuth ");
+        let mut res = "OAuth ".to_string();
 
         if let Some(realm) = &self.realm {
             res.push_str(&format!("realm=\"{}\", ", realm));
@@ -94,7 +138,7 @@ impl Oauth1 {
         res
     }
 
-    fn get_value_by_key<'a>(key: &str, data: &'a QueryPair) -> Option<String> {
+    fn get_value_by_key(key: &str, data: &QueryPair) -> Option<String> {
         data.iter()
             .find_map(|(k, v)| if k == key { Some(v.clone()) } else { None })
     }
@@ -112,24 +156,23 @@ impl Oauth1 {
         let uri = uri.into();
         let encoded_uri = url_escape::encode_www_form_urlencoded(&uri);
 
-        let raw_query_part = serde_urlencoded::to_string(&query_poll).unwrap_or(String::new());
+        let raw_query_part = serde_urlencoded::to_string(&query_poll).unwrap_or_default();
         let encoded_query = url_escape::encode_www_form_urlencoded(&raw_query_part);
 
         let sign_base = format!("{}&{}&{}", method.into(), encoded_uri, encoded_query);
-        let sign = Self::hmac_sha1_sign(sign_base, self.sign_key.clone());
+        let sign = Self::hmac_sha1_sign(sign_base, &self.sign_key);
 
         self.oauth_signature = sign;
         self.oauth_callback = Self::get_value_by_key("oauth_callback", &query_poll);
         self.oauth_verifier = Self::get_value_by_key("oauth_verifier", &query_poll);
-        self.realm = Some(uri.into());
+        self.realm = Some(uri);
 
         self
     }
 
-    fn hmac_sha1_sign(sign_url: String, sign_key: String) -> String {
-        let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, sign_key.as_bytes());
-        let h = hmac::sign(&key, sign_url.as_bytes());
-        let sign = general_purpose::STANDARD.encode(&h);
+    fn hmac_sha1_sign(sign_url: String, key: &hmac::Key) -> String {
+        let h = hmac::sign(key, sign_url.as_bytes());
+        let sign = general_purpose::STANDARD.encode(h);
         url_escape::encode_www_form_urlencoded(&sign).to_string()
     }
 
@@ -353,4 +396,18 @@ mod tests {
                    oauth_version=\"1.0\""
         );
     }
+
+    #[test]
+    fn test_signer() {
+        let mut signer = Signer::new(Secret::new("c1", "c2", None, None));
+        signer.update_token("t3", "t4");
+        let oauth = signer
+            .oauth()
+            .test_set_nonce("aabbcc123")
+            .test_set_timestamp("1191242096")
+            .sign("POST", "https://www.example.com/API/foo", "")
+            .to_header();
+        assert!(oauth.contains("oauth_signature=\"wStrZYCwsImMLtk4CwB3whzGoOA%3D\""));
+        assert!(oauth.contains("oauth_token=\"t3\""));
+    }
 }
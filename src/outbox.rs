@@ -0,0 +1,91 @@
+use crate::models::Qualifier;
+use crate::plurk::{Plurk, PlurkError};
+use crate::runtime::PlurkRuntime;
+use tokio::sync::mpsc;
+
+/// Queue of plurks posted in order by a background task, see
+/// [`PlurkRuntime::spawn_outbox`]. Clones feed the same queue.
+#[derive(Debug, Clone)]
+pub struct Outbox {
+    tx: mpsc::Sender<(String, Qualifier)>,
+}
+
+impl Outbox {
+    /// Queue `content`, waiting while the queue is full. Fails once the runtime has shut
+    /// down.
+    pub async fn send<S: Into<String>>(
+        &self,
+        content: S,
+        qualifier: Qualifier,
+    ) -> Result<(), PlurkError> {
+        self.tx
+            .send((content.into(), qualifier))
+            .await
+            .map_err(|_| PlurkError::IOError(String::from("Outbox is closed")))
+    }
+}
+
+impl PlurkRuntime {
+    /// Post the plurks queued on the returned [`Outbox`], holding at most `capacity` of
+    /// them at a time.
+    ///
+    /// On shutdown the queue stops accepting plurks and everything already queued is
+    /// posted before the task returns. A failing post is handed to `on_error` with its
+    /// content and does not stop the outbox.
+    pub fn spawn_outbox<E>(&mut self, plurk: Plurk, capacity: usize, mut on_error: E) -> Outbox
+    where
+        E: FnMut(String, PlurkError) + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::channel::<(String, Qualifier)>(capacity);
+        self.spawn(move |token| async move {
+            loop {
+                let (content, qualifier) = tokio::select! {
+                    biased;
+                    _ = token.cancelled() => break,
+                    item = rx.recv() => match item {
+                        Some(item) => item,
+                        None => return Ok(()),
+                    },
+                };
+                if let Err(e) = plurk.add_plurk(&content, qualifier).await {
+                    on_error(content, e);
+                }
+            }
+            rx.close();
+            while let Some((content, qualifier)) = rx.recv().await {
+                if let Err(e) = plurk.add_plurk(&content, qualifier).await {
+                    on_error(content, e);
+                }
+            }
+            Ok(())
+        });
+        Outbox { tx }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_shutdown_outbox() {
+        use super::*;
+        use crate::api::timeline::TIMELINE_PLURK_ADD_URL;
+        use crate::testing::{fixtures, MockServer};
+
+        let server = MockServer::new().mock(TIMELINE_PLURK_ADD_URL, fixtures::PLURK_ADD);
+        let mut runtime = PlurkRuntime::new();
+        let outbox = runtime.spawn_outbox(server.plurk(), 4, |_, _| {});
+        for content in ["a", "b", "c"] {
+            outbox.send(content, Qualifier::Says).await.unwrap();
+        }
+
+        assert!(runtime.shutdown().await.is_empty());
+        let posted: Vec<_> = server
+            .requests()
+            .iter()
+            .map(|r| r.param("content").unwrap().to_string())
+            .collect();
+        assert_eq!(posted, ["a", "b", "c"]);
+        assert!(outbox.send("d", Qualifier::Says).await.is_err());
+    }
+}
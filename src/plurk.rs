@@ -1,26 +1,94 @@
-use crate::oauth1::Oauth1;
+use crate::api::timeline::BULK_DELETE_DELAY;
+use crate::guard::DuplicateGuard;
+use crate::middleware::Middlewares;
+use crate::models::UnknownVariant;
+use crate::oauth1::Signer;
+use crate::rate_limit::RateLimiter;
 use crate::secret::{Secret, SecretError};
-use reqwest::{self, multipart, Body, RequestBuilder, Response};
-use serde::{Deserialize, Serialize};
+use crate::transport::{PlurkTransport, Transport};
+use bytes::Bytes;
+#[cfg(not(feature = "simd-json"))]
+use futures::TryStreamExt;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use reqwest::{
+    self,
+    header::{HeaderMap, HeaderValue},
+    multipart, Body, Method, Request, RequestBuilder, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(not(feature = "simd-json"))]
+use std::io;
 use std::{
     fmt::{self, Debug},
+    future::Future,
     path::Path,
+    time::{Duration, Instant},
 };
 use tokio::fs::File;
 use tokio_util::codec::{BytesCodec, FramedRead};
-use url::Position;
+#[cfg(not(feature = "simd-json"))]
+use tokio_util::io::{StreamReader, SyncIoBridge};
+use url::{Position, Url};
+
+/// Run `fut` inside a span built from the remaining arguments when the `tracing` feature
+/// is enabled.
+macro_rules! traced {
+    ($fut:expr, $($span:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        let fut = tracing::Instrument::instrument($fut, tracing::info_span!($($span)+));
+        #[cfg(not(feature = "tracing"))]
+        let fut = $fut;
+        fut
+    }};
+}
 
 const BASE_URL: &str = "https://www.plurk.com";
 const REQUEST_TOKEN_URL: &str = "/OAuth/request_token";
 const AUTHORIZE_URL: &str = "/OAuth/authorize";
 const ACCESS_TOKEN_URL: &str = "/OAuth/access_token";
+const CORRELATION_ID_HEADER: &str = "X-Correlation-ID";
 
 #[derive(Debug)]
 pub enum PlurkError {
     ReqwestError(reqwest::Error),
     APICallError(String),
+    /// Plurk answered with an error status. `text` is the `error_text` of the body, or
+    /// the whole body when it has none.
+    Api {
+        code: u16,
+        text: String,
+    },
     AuthError(String),
     SecretError(SecretError),
+    InvalidArgument(String),
+    IOError(String),
+    /// An error of the request sent with the given correlation id.
+    Correlated(String, Box<PlurkError>),
+}
+
+impl PlurkError {
+    /// The correlation id of the failed request, when correlation ids are enabled.
+    pub fn correlation_id(&self) -> Option<&str> {
+        match self {
+            Self::Correlated(id, _) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The error without its correlation id.
+    pub fn inner(&self) -> &PlurkError {
+        match self {
+            Self::Correlated(_, e) => e.inner(),
+            e => e,
+        }
+    }
+
+    fn correlated(self, id: Option<String>) -> Self {
+        match id {
+            Some(id) => Self::Correlated(id, Box::new(self)),
+            None => self,
+        }
+    }
 }
 
 impl fmt::Display for PlurkError {
@@ -28,18 +96,179 @@ impl fmt::Display for PlurkError {
         match self {
             Self::ReqwestError(e) => write!(f, "reqwest error: {}", e),
             Self::APICallError(e) => write!(f, "API Request Error: {}", e),
+            Self::Api { code, text } => write!(f, "Plurk API Error {}: {}", code, text),
             Self::AuthError(e) => write!(f, "Authorization Error: {}", e),
             Self::SecretError(e) => write!(f, "Secret Error: {}", e),
+            Self::InvalidArgument(e) => write!(f, "Invalid Argument: {}", e),
+            Self::IOError(e) => write!(f, "IO Error: {}", e),
+            Self::Correlated(id, e) => write!(f, "{} (correlation id: {})", e, id),
         }
     }
 }
 
+impl From<UnknownVariant> for PlurkError {
+    fn from(e: UnknownVariant) -> Self {
+        Self::InvalidArgument(e.to_string())
+    }
+}
+
+/// Overrides for a single request, see [`Plurk::request_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Replaces the timeout of the client, e.g. a longer one for comet long-polls.
+    pub timeout: Option<Duration>,
+}
+
+/// A response read into memory, see [`Plurk::request_bytes`].
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl RawResponse {
+    pub(crate) async fn read(res: Response) -> Result<Self, PlurkError> {
+        Ok(Self {
+            status: res.status(),
+            headers: res.headers().clone(),
+            body: res.bytes().await.map_err(PlurkError::ReqwestError)?,
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Plurk {
+    signer: Signer,
+    /// Shared by all requests and clones so connections and TLS sessions are reused.
+    pub(crate) client: reqwest::Client,
+    transport: Transport,
+    base_url: String,
+    pub(crate) guard: Option<DuplicateGuard>,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) bulk_delete_delay: Duration,
+    pub(crate) middlewares: Middlewares,
+    correlation_ids: bool,
+}
+
+/// HTTP client configuration for [`Plurk`], created by [`Plurk::builder`].
+#[derive(Debug)]
+pub struct PlurkBuilder {
     secret: Secret,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<reqwest::Proxy>,
+    client: Option<reqwest::Client>,
+    transport: Option<Transport>,
+    base_url: Option<String>,
+}
+
+impl PlurkBuilder {
+    pub fn token<S: Into<String>>(mut self, token_key: S, token_secret: S) -> Self {
+        self.secret.update_token_mut(token_key, token_secret);
+        self
+    }
+
+    /// Timeout of a whole request, from connecting until the body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Send the requests with `client`. The timeout, user agent and proxy set on this
+    /// builder are ignored then; configure them on `client` instead.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Send the signed requests through `transport` instead of the HTTP client, e.g. a
+    /// fake one in tests.
+    pub fn transport<T>(mut self, transport: T) -> Self
+    where
+        T: PlurkTransport + 'static,
+    {
+        self.transport = Some(Transport::new(transport));
+        self
+    }
+
+    /// Send API requests to `base_url` instead of `https://www.plurk.com`, e.g. a local
+    /// mock server in tests.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Plurk, PlurkError> {
+        let base_url = match self.base_url {
+            Some(url) => {
+                Url::parse(&url)
+                    .map_err(|e| PlurkError::InvalidArgument(format!("{}: {}", url, e)))?;
+                url.trim_end_matches('/').to_string()
+            }
+            None => BASE_URL.to_string(),
+        };
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = reqwest::Client::builder();
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent);
+                }
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+                builder.build().map_err(PlurkError::ReqwestError)?
+            }
+        };
+        let transport = self
+            .transport
+            .unwrap_or_else(|| Transport::new(client.clone()));
+        Ok(Plurk::from_parts(self.secret, client, transport, base_url))
+    }
 }
 
 impl Plurk {
+    /// Every constructor ends up here, so the defaults live in one place.
+    fn from_parts(
+        secret: Secret,
+        client: reqwest::Client,
+        transport: Transport,
+        base_url: String,
+    ) -> Self {
+        Self {
+            signer: Signer::new(secret),
+            client,
+            transport,
+            base_url,
+            guard: None,
+            rate_limiter: None,
+            bulk_delete_delay: BULK_DELETE_DELAY,
+            middlewares: Middlewares::default(),
+            correlation_ids: false,
+        }
+    }
+
+    /// Default HTTP client, talking to `https://www.plurk.com`.
+    fn with_secret(secret: Secret) -> Self {
+        let client = reqwest::Client::new();
+        let transport = Transport::new(client.clone());
+        Self::from_parts(secret, client, transport, BASE_URL.to_string())
+    }
+
     pub fn new<TString>(
         consumer_key: TString,
         consumer_secret: TString,
@@ -49,57 +278,106 @@ impl Plurk {
     where
         TString: Into<String>,
     {
-        Self {
-            secret: Secret::new(consumer_key, consumer_secret, token_key, token_secret),
+        Self::with_secret(Secret::new(
+            consumer_key,
+            consumer_secret,
+            token_key,
+            token_secret,
+        ))
+    }
+
+    /// Configure the HTTP client before creating the API, e.g.
+    /// `Plurk::builder(key, secret).timeout(Duration::from_secs(10)).build()?`.
+    pub fn builder<S: Into<String>>(consumer_key: S, consumer_secret: S) -> PlurkBuilder {
+        PlurkBuilder {
+            secret: Secret::new(consumer_key.into(), consumer_secret.into(), None, None),
+            timeout: None,
+            user_agent: None,
+            proxy: None,
+            client: None,
+            transport: None,
+            base_url: None,
         }
     }
 
+    /// Send a random id in the `X-Correlation-ID` header of every request and attach it
+    /// to the errors of that request, see [`PlurkError::correlation_id`].
+    pub fn with_correlation_ids(mut self, enabled: bool) -> Self {
+        self.correlation_ids = enabled;
+        self
+    }
+
+    fn new_correlation_id(&self) -> Option<String> {
+        self.correlation_ids.then(|| {
+            thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(16)
+                .map(char::from)
+                .collect()
+        })
+    }
+
     pub fn is_auth(&self) -> bool {
-        self.secret.get_token_key().is_some()
+        self.signer.secret().get_token_key().is_some()
     }
 
     fn update_token<S>(&mut self, token_key: S, token_secret: S)
     where
         S: Into<String>,
     {
-        self.secret.update_token_mut(token_key, token_secret);
+        self.signer.update_token(token_key, token_secret);
     }
 
-    fn prep_cmd<I>(api: I) -> String
+    /// Absolute URLs, e.g. of the comet server, are used as they are.
+    fn prep_cmd<I>(&self, api: I) -> String
     where
         I: Into<String>,
     {
-        format!("{}{}", BASE_URL, api.into())
+        let api = api.into();
+        if api.starts_with("https://") || api.starts_with("http://") {
+            api
+        } else {
+            format!("{}{}", self.base_url, api)
+        }
+    }
+
+    /// The parameters covered by the OAuth signature: those of the URL followed by the
+    /// form body, if any.
+    fn signed_params(request: &Request) -> String {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+        match request.url().query() {
+            Some(query) if body.is_empty() => query.to_string(),
+            Some(query) => format!("{}&{}", query, body),
+            None => body.to_string(),
+        }
     }
 
-    fn sign(&self, builder: RequestBuilder) -> RequestBuilder {
-        let (client, inner) = builder.build_split();
-        // TODO: Remove the unwrap
-        let request = inner.unwrap();
+    fn sign(&self, builder: RequestBuilder) -> Result<Request, PlurkError> {
+        let mut request = builder.build().map_err(PlurkError::ReqwestError)?;
 
         let url = &request.url()[..Position::AfterPath];
         let url = url.to_string();
         let method = request.method().to_string();
-        let query = if let Some(raw_body) = request.body() {
-            if let Some(raw_body) = raw_body.as_bytes() {
-                String::from_utf8_lossy(raw_body).to_string()
-            } else {
-                String::new()
-            }
-        } else {
-            String::new()
-        };
-
-        let oauth = Oauth1::new(self.secret.clone())
-            .sign(method, url, query)
-            .to_header();
+        let query = Plurk::signed_params(&request);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(%method, %url, "signing request");
 
-        let builder = RequestBuilder::from_parts(client, request);
-
-        builder.header(reqwest::header::AUTHORIZATION, oauth)
+        let oauth = self.signer.oauth().sign(method, url, query).to_header();
+        let oauth = HeaderValue::from_str(&oauth)
+            .map_err(|e| PlurkError::InvalidArgument(format!("OAuth header: {}", e)))?;
+        request
+            .headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, oauth);
+        Ok(request)
     }
 
-    async fn file_to_multipart<TPath>(file: (String, TPath)) -> Result<multipart::Form, PlurkError>
+    pub(crate) async fn file_to_multipart<TPath>(
+        file: (String, TPath),
+    ) -> Result<multipart::Form, PlurkError>
     where
         TPath: AsRef<Path>,
     {
@@ -140,40 +418,371 @@ impl Plurk {
         TString: Into<String>,
         TPath: AsRef<Path>,
     {
-        // Accept order file > query
-        let query = if file.is_some() { None } else { query };
+        self.request_with(api, query, file, &RequestOptions::default())
+            .await
+    }
 
-        let request = reqwest::Client::new().post(Plurk::prep_cmd(api));
+    /// Like [`Self::request`] with per-request `options`.
+    pub async fn request_with<TQuery, TString, TPath>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+        file: Option<(String, TPath)>,
+        options: &RequestOptions,
+    ) -> Result<Response, PlurkError>
+    where
+        TQuery: Serialize,
+        TString: Into<String>,
+        TPath: AsRef<Path>,
+    {
+        let id = self.new_correlation_id();
+        let res = async {
+            // Accept order file > query
+            let form = match file {
+                Some(f) => Some(Plurk::file_to_multipart(f).await?),
+                None => None,
+            };
+            self.send_request(Method::POST, api, query, form, id.as_deref(), options)
+                .await
+        };
+        res.await.map_err(|e| e.correlated(id))
+    }
 
-        // Add query
-        let request = if let Some(q) = query {
-            request.form(&q)
-        } else {
-            request
+    /// Like [`Self::request`], deserializing the JSON body into `T`. Responses that are
+    /// not JSON, e.g. an HTML error page, fail with [`PlurkError::APICallError`].
+    pub async fn request_json<T, TQuery, TString, TPath>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+        file: Option<(String, TPath)>,
+    ) -> Result<T, PlurkError>
+    where
+        T: DeserializeOwned + Send + 'static,
+        TQuery: Serialize,
+        TString: Into<String>,
+        TPath: AsRef<Path>,
+    {
+        let res = self.request(api, query, file).await?;
+        Plurk::check_json(&res)?;
+        Plurk::parse_json(res).await
+    }
+
+    /// Like [`Self::request`], reading the whole body as bytes, e.g. for endpoints that do
+    /// not answer JSON.
+    pub async fn request_bytes<TQuery, TString, TPath>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+        file: Option<(String, TPath)>,
+    ) -> Result<RawResponse, PlurkError>
+    where
+        TQuery: Serialize,
+        TString: Into<String>,
+        TPath: AsRef<Path>,
+    {
+        RawResponse::read(self.request(api, query, file).await?).await
+    }
+
+    /// Like [`Self::get`], reading the whole body as bytes, e.g. for images or the comet
+    /// channel.
+    pub async fn get_bytes<TQuery, TString>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+    ) -> Result<RawResponse, PlurkError>
+    where
+        TQuery: Serialize,
+        TString: Into<String>,
+    {
+        RawResponse::read(self.get(api, query).await?).await
+    }
+
+    /// A missing content type is accepted, Plurk omits it on some endpoints.
+    fn check_json(res: &Response) -> Result<(), PlurkError> {
+        match res.headers().get(reqwest::header::CONTENT_TYPE) {
+            Some(t) if !t.to_str().unwrap_or_default().contains("json") => {
+                Err(PlurkError::APICallError(format!(
+                    "Expected a JSON response, got {}",
+                    t.to_str().unwrap_or("an invalid content type")
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Like [`Self::request`] as a GET, with `query` in the URL. `api` is either an API
+    /// path or an absolute URL for resources outside the API such as comet channels.
+    pub async fn get<TQuery, TString>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+    ) -> Result<Response, PlurkError>
+    where
+        TQuery: Serialize,
+        TString: Into<String>,
+    {
+        self.get_with(api, query, &RequestOptions::default()).await
+    }
+
+    /// Like [`Self::get`] with per-request `options`.
+    pub async fn get_with<TQuery, TString>(
+        &self,
+        api: TString,
+        query: Option<TQuery>,
+        options: &RequestOptions,
+    ) -> Result<Response, PlurkError>
+    where
+        TQuery: Serialize,
+        TString: Into<String>,
+    {
+        let id = self.new_correlation_id();
+        self.send_request(Method::GET, api, query, None, id.as_deref(), options)
+            .await
+            .map_err(|e| e.correlated(id))
+    }
+
+    async fn send_request<TQuery, TString>(
+        &self,
+        method: Method,
+        api: TString,
+        query: Option<TQuery>,
+        form: Option<multipart::Form>,
+        correlation_id: Option<&str>,
+        options: &RequestOptions,
+    ) -> Result<Response, PlurkError>
+    where
+        TQuery: Serialize,
+        TString: Into<String>,
+    {
+        let api = api.into();
+        #[cfg(feature = "tracing")]
+        let start = Instant::now();
+        let res = traced!(
+            self.send_untraced(method.clone(), &api, query, form, correlation_id, options),
+            "plurk.request",
+            %method,
+            api = %api,
+            correlation_id = correlation_id.unwrap_or_default()
+        )
+        .await;
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_ms = start.elapsed().as_millis() as u64;
+            match &res {
+                Ok(res) => tracing::debug!(
+                    %method, api = %api, status = res.status().as_u16(), elapsed_ms,
+                    "request succeeded"
+                ),
+                Err(e) => tracing::warn!(
+                    %method, api = %api, error = %e, elapsed_ms,
+                    "request failed"
+                ),
+            }
+        }
+        res
+    }
+
+    async fn send_untraced<TQuery>(
+        &self,
+        method: Method,
+        api: &str,
+        query: Option<TQuery>,
+        form: Option<multipart::Form>,
+        correlation_id: Option<&str>,
+        options: &RequestOptions,
+    ) -> Result<Response, PlurkError>
+    where
+        TQuery: Serialize,
+    {
+        let is_get = method == Method::GET;
+        let request = self.client.request(method, self.prep_cmd(api));
+        let request = match options.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
         };
 
-        // Add multipart for image
-        let request = if let Some(f) = file {
-            let form = Plurk::file_to_multipart(f).await?;
-            request.multipart(form)
+        // Add multipart for image, otherwise the query
+        let request = match (form, query) {
+            (Some(form), _) => request.multipart(form),
+            (None, Some(q)) if is_get => request.query(&q),
+            (None, Some(q)) => request.form(&q),
+            (None, None) => request,
+        };
+
+        let request = if let Some(id) = correlation_id {
+            request.header(CORRELATION_ID_HEADER, id)
         } else {
             request
         };
 
+        self.dispatch(request, true).await
+    }
+
+    /// GET `url` outside the API, e.g. an uploaded image, without signing it. Like API
+    /// calls it goes through the rate limiter, the middlewares and the transport.
+    #[cfg(feature = "export")]
+    pub(crate) async fn get_unsigned(&self, url: &str) -> Result<Response, PlurkError> {
+        self.dispatch(self.client.get(url), false).await
+    }
+
+    /// Send `request` through the rate limiter, the middlewares and the transport.
+    async fn dispatch(
+        &self,
+        request: RequestBuilder,
+        signed: bool,
+    ) -> Result<Response, PlurkError> {
+        // Wait before signing, the OAuth timestamp must be fresh when the request is sent
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+
         // Sign oauth1
-        let request = self.sign(request);
+        let mut request = if signed {
+            self.sign(request)?
+        } else {
+            request.build().map_err(PlurkError::ReqwestError)?
+        };
+        self.middlewares.before_send(&mut request)?;
+        let (method, url) = (request.method().clone(), request.url().clone());
+        let start = Instant::now();
+        let res = self.transport.execute(request).await;
+        self.middlewares
+            .after_receive(&method, &url, res.as_ref(), start.elapsed());
 
-        request
-            .send()
-            .await
-            .map_err(|e| PlurkError::ReqwestError(e))
+        Plurk::error_for_status(res?).await
+    }
+
+    /// Turn a response with an error status into [`PlurkError::Api`].
+    async fn error_for_status(res: Response) -> Result<Response, PlurkError> {
+        #[derive(Deserialize)]
+        struct ErrorText {
+            error_text: String,
+        }
+
+        let status = res.status();
+        if status.is_success() {
+            return Ok(res);
+        }
+        let body = res.text().await.map_err(PlurkError::ReqwestError)?;
+        let text = match serde_json::from_str::<ErrorText>(&body) {
+            Ok(e) => e.error_text,
+            Err(_) => body,
+        };
+        Err(PlurkError::Api {
+            code: status.as_u16(),
+            text,
+        })
+    }
+
+    pub(crate) async fn call<TQuery, T>(
+        &self,
+        api: &str,
+        query: Option<TQuery>,
+    ) -> Result<T, PlurkError>
+    where
+        TQuery: Serialize,
+        T: DeserializeOwned + Send + 'static,
+    {
+        let id = self.new_correlation_id();
+        let res = async {
+            let res = self
+                .send_request(
+                    Method::POST,
+                    api,
+                    query,
+                    None,
+                    id.as_deref(),
+                    &RequestOptions::default(),
+                )
+                .await?;
+            Plurk::parse_json(res).await
+        };
+        res.await.map_err(|e| e.correlated(id))
+    }
+
+    pub(crate) async fn call_with_form<T>(
+        &self,
+        api: &str,
+        form: multipart::Form,
+    ) -> Result<T, PlurkError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let id = self.new_correlation_id();
+        let res = async {
+            let res = self
+                .send_request(
+                    Method::POST,
+                    api,
+                    None::<()>,
+                    Some(form),
+                    id.as_deref(),
+                    &RequestOptions::default(),
+                )
+                .await?;
+            Plurk::parse_json(res).await
+        };
+        res.await.map_err(|e| e.correlated(id))
+    }
+
+    /// For endpoints answering `{"success_text": "ok"}`.
+    pub(crate) async fn call_ok<TQuery>(
+        &self,
+        api: &str,
+        query: Option<TQuery>,
+    ) -> Result<(), PlurkError>
+    where
+        TQuery: Serialize,
+    {
+        let res: serde_json::Value = self.call(api, query).await?;
+        Plurk::check_success(&res)
+    }
+
+    /// Some endpoints report failures as `error_text` without an error status.
+    fn check_success(res: &serde_json::Value) -> Result<(), PlurkError> {
+        match res.get("error_text").and_then(|e| e.as_str()) {
+            Some(text) => Err(PlurkError::Api {
+                code: 200,
+                text: text.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Deserialize a successful response without first buffering the whole body into a
+    /// `String`: the body is streamed into `serde_json` on a blocking thread, or parsed in
+    /// place with `simd-json` when that feature is enabled.
+    async fn parse_json<T>(res: Response) -> Result<T, PlurkError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        #[cfg(feature = "simd-json")]
+        {
+            let mut body = res
+                .bytes()
+                .await
+                .map_err(PlurkError::ReqwestError)?
+                .to_vec();
+            simd_json::serde::from_slice(&mut body)
+                .map_err(|e| PlurkError::APICallError(e.to_string()))
+        }
+
+        #[cfg(not(feature = "simd-json"))]
+        {
+            let body = res.bytes_stream().map_err(io::Error::other);
+            let reader = SyncIoBridge::new(StreamReader::new(body));
+            tokio::task::spawn_blocking(move || serde_json::from_reader(io::BufReader::new(reader)))
+                .await
+                .map_err(|e| PlurkError::APICallError(e.to_string()))?
+                .map_err(|e| PlurkError::APICallError(e.to_string()))
+        }
     }
 
     pub fn get_auth_url(&self) -> Result<String, PlurkError> {
-        if let Some(token_key) = self.secret.get_token_key() {
+        if let Some(token_key) = self.signer.secret().get_token_key() {
             Ok(format!(
                 "{}?oauth_token={}",
-                Plurk::prep_cmd(AUTHORIZE_URL),
+                self.prep_cmd(AUTHORIZE_URL),
                 token_key
             ))
         } else {
@@ -196,16 +805,22 @@ impl Plurk {
     }
 
     pub async fn request_auth(&mut self) -> Result<(), PlurkError> {
-        let resp = self
-            .request(
-                REQUEST_TOKEN_URL,
-                Some([("oauth_callback", "oob")]),
-                None::<(String, String)>,
-            )
-            .await?
-            .text()
-            .await
-            .map_err(|e| PlurkError::ReqwestError(e))?;
+        let resp = traced!(
+            async {
+                self.request(
+                    REQUEST_TOKEN_URL,
+                    Some([("oauth_callback", "oob")]),
+                    None::<(String, String)>,
+                )
+                .await?
+                .text()
+                .await
+                .map_err(PlurkError::ReqwestError)
+            },
+            "plurk.auth",
+            step = "request_token"
+        )
+        .await?;
 
         if let Some((key, secret)) = Plurk::parse_oauth_token(resp) {
             self.update_token(key, secret);
@@ -218,16 +833,22 @@ impl Plurk {
     where
         T: AsRef<str> + Debug + Serialize,
     {
-        let resp = self
-            .request(
-                ACCESS_TOKEN_URL,
-                Some([("oauth_verifier", &pin)]),
-                None::<(String, String)>,
-            )
-            .await?
-            .text()
-            .await
-            .map_err(|e| PlurkError::ReqwestError(e))?;
+        let resp = traced!(
+            async {
+                self.request(
+                    ACCESS_TOKEN_URL,
+                    Some([("oauth_verifier", &pin)]),
+                    None::<(String, String)>,
+                )
+                .await?
+                .text()
+                .await
+                .map_err(PlurkError::ReqwestError)
+            },
+            "plurk.auth",
+            step = "access_token"
+        )
+        .await?;
 
         if let Some((key, secret)) = Plurk::parse_oauth_token(resp) {
             self.update_token(key, secret);
@@ -235,34 +856,62 @@ impl Plurk {
         Ok(())
     }
 
+    /// Run the PIN based OAuth flow: request a token, hand the authorization URL to
+    /// `open_url`, then verify the PIN produced by `get_pin`.
+    ///
+    /// `get_pin` is only awaited after `open_url` returns, so GUI and TUI applications
+    /// can show the URL and prompt for the PIN their own way.
+    pub async fn authorize_interactive<O, P, S>(
+        &mut self,
+        open_url: O,
+        get_pin: P,
+    ) -> Result<(), PlurkError>
+    where
+        O: FnOnce(&str),
+        P: Future<Output = S>,
+        S: AsRef<str>,
+    {
+        self.request_auth().await?;
+        let url = self.get_auth_url()?;
+        open_url(&url);
+        let pin = get_pin.await;
+        self.verify_auth(pin.as_ref().trim()).await
+    }
+
     pub fn to_toml<P>(&self, path: P) -> Result<(), PlurkError>
     where
         P: AsRef<Path>,
     {
-        self.secret
+        self.signer
+            .secret()
             .to_toml(path)
-            .map_err(|e| PlurkError::SecretError(e))
+            .map_err(PlurkError::SecretError)
     }
 
     pub fn from_toml<P>(path: P) -> Result<Self, PlurkError>
     where
         P: AsRef<Path>,
     {
-        Ok(Self {
-            secret: Secret::from_toml(path).map_err(|e| PlurkError::SecretError(e))?,
-        })
+        Secret::from_toml(path)
+            .map(Self::with_secret)
+            .map_err(PlurkError::SecretError)
     }
 }
 
 impl fmt::Display for Plurk {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Plurk API {} ({})", self.secret.get_consumer_key(), {
-            if self.is_auth() {
-                "Authorized"
-            } else {
-                "Unauthorized"
+        write!(
+            f,
+            "Plurk API {} ({})",
+            self.signer.secret().get_consumer_key(),
+            {
+                if self.is_auth() {
+                    "Authorized"
+                } else {
+                    "Unauthorized"
+                }
             }
-        })
+        )
     }
 }
 
@@ -281,6 +930,14 @@ mod tests {
             PlurkError::SecretError(SecretError::IOError("foo".into()))
         );
         assert_eq!(res, "Secret Error: IO Error: foo");
+        let res = format!("{}", PlurkError::InvalidArgument("foo".into()));
+        assert_eq!(res, "Invalid Argument: foo");
+        let res = format!("{}", PlurkError::IOError("foo".into()));
+        assert_eq!(res, "IO Error: foo");
+        let e = PlurkError::IOError("foo".into()).correlated(Some("abc".into()));
+        assert_eq!(format!("{}", e), "IO Error: foo (correlation id: abc)");
+        assert_eq!(e.correlation_id(), Some("abc"));
+        assert!(matches!(e.inner(), PlurkError::IOError(_)));
     }
 
     #[test]
@@ -302,6 +959,175 @@ mod tests {
         assert_eq!(res, "Plurk API 123 (Authorized)");
     }
 
+    #[test]
+    fn test_builder() {
+        let plurk = Plurk::builder("123", "abc")
+            .timeout(Duration::from_secs(5))
+            .user_agent("plurk-bot/1.0")
+            .build()
+            .unwrap();
+        assert_eq!(plurk.to_string(), "Plurk API 123 (Unauthorized)");
+
+        let plurk = Plurk::builder("123", "abc")
+            .token("ttt", "AAA")
+            .proxy(reqwest::Proxy::all("http://127.0.0.1:8080").unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(plurk.to_string(), "Plurk API 123 (Authorized)");
+
+        let plurk = Plurk::builder("123", "abc")
+            .client(reqwest::Client::new())
+            .base_url("http://127.0.0.1:8080/")
+            .build()
+            .unwrap();
+        assert_eq!(
+            plurk.prep_cmd("/APP/echo"),
+            "http://127.0.0.1:8080/APP/echo"
+        );
+
+        let res = Plurk::builder("123", "abc").base_url("not a url").build();
+        assert!(matches!(res, Err(PlurkError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_signed_params() {
+        let plurk = Plurk::new("123", "abc", None, None);
+        let client = reqwest::Client::new();
+        let request = client
+            .get(plurk.prep_cmd("https://comet03.plurk.com/comet"))
+            .query(&[("channel", "a b"), ("offset", "3")])
+            .build()
+            .unwrap();
+        assert_eq!(Plurk::signed_params(&request), "channel=a+b&offset=3");
+
+        let request = client
+            .post(plurk.prep_cmd("/APP/echo"))
+            .query(&[("a", "1")])
+            .form(&[("data", "ping")])
+            .build()
+            .unwrap();
+        assert_eq!(request.url().as_str(), "https://www.plurk.com/APP/echo?a=1");
+        assert_eq!(Plurk::signed_params(&request), "a=1&data=ping");
+    }
+
+    #[test]
+    fn test_check_success() {
+        assert!(Plurk::check_success(&serde_json::json!({"success_text": "ok"})).is_ok());
+        let res = Plurk::check_success(&serde_json::json!({"error_text": "Plurk not found"}));
+        assert!(matches!(
+            res,
+            Err(PlurkError::Api { code: 200, text }) if text == "Plurk not found"
+        ));
+    }
+
+    #[test]
+    fn test_correlation_id() {
+        let plurk = Plurk::new("123", "abc", None, None);
+        assert_eq!(plurk.new_correlation_id(), None);
+        let plurk = plurk.with_correlation_ids(true);
+        let id = plurk.new_correlation_id().unwrap();
+        assert_eq!(id.len(), 16);
+        assert_ne!(plurk.new_correlation_id(), Some(id));
+    }
+
+    fn response(status: u16, body: &str) -> Response {
+        http::Response::builder()
+            .status(status)
+            .body(body.to_string())
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_parse_json() {
+        let res: serde_json::Value = Plurk::parse_json(response(200, r#"{"a": [1, 2]}"#))
+            .await
+            .unwrap();
+        assert_eq!(res["a"][1], 2);
+
+        let res = Plurk::parse_json::<serde_json::Value>(response(200, "{")).await;
+        assert!(matches!(res, Err(PlurkError::APICallError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_raw_response() {
+        let res: Response = http::Response::builder()
+            .status(201)
+            .header("content-type", "image/png")
+            .body(b"\x89PNG".to_vec())
+            .unwrap()
+            .into();
+        let raw = RawResponse::read(res).await.unwrap();
+        assert_eq!(raw.status, StatusCode::CREATED);
+        assert_eq!(raw.headers["content-type"], "image/png");
+        assert_eq!(raw.body.as_ref(), b"\x89PNG");
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        use crate::middleware::PlurkMiddleware;
+        use std::sync::{Arc, Mutex};
+
+        struct Timeouts(Arc<Mutex<Vec<Option<Duration>>>>);
+
+        impl PlurkMiddleware for Timeouts {
+            fn before_send(&self, request: &mut Request) -> Result<(), PlurkError> {
+                self.0.lock().unwrap().push(request.timeout().copied());
+                Ok(())
+            }
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let plurk = Plurk::builder("key", "secret")
+            .base_url("http://127.0.0.1:1")
+            .build()
+            .unwrap()
+            .with_middleware(Timeouts(seen.clone()));
+        let options = RequestOptions {
+            timeout: Some(Duration::from_secs(90)),
+        };
+        assert!(plurk.get("/APP/echo", None::<()>).await.is_err());
+        assert!(plurk
+            .get_with("/APP/echo", None::<()>, &options)
+            .await
+            .is_err());
+        assert_eq!(*seen.lock().unwrap(), [None, Some(Duration::from_secs(90))]);
+    }
+
+    #[test]
+    fn test_check_json() {
+        let with_type = |content_type: &str| -> Response {
+            http::Response::builder()
+                .header("content-type", content_type)
+                .body(String::from("{}"))
+                .unwrap()
+                .into()
+        };
+        assert!(Plurk::check_json(&with_type("application/json")).is_ok());
+        assert!(Plurk::check_json(&with_type("application/json; charset=utf-8")).is_ok());
+        assert!(Plurk::check_json(&response(200, "{}")).is_ok());
+        let res = Plurk::check_json(&with_type("text/html"));
+        assert!(matches!(res, Err(PlurkError::APICallError(e)) if e.ends_with("text/html")));
+    }
+
+    #[tokio::test]
+    async fn test_error_for_status() {
+        assert!(Plurk::error_for_status(response(200, "{}")).await.is_ok());
+
+        let res =
+            Plurk::error_for_status(response(400, r#"{"error_text": "Plurk not found"}"#)).await;
+        assert!(matches!(
+            res,
+            Err(PlurkError::Api { code: 400, text }) if text == "Plurk not found"
+        ));
+
+        let res = Plurk::error_for_status(response(502, "Bad Gateway")).await;
+        assert!(matches!(
+            res,
+            Err(PlurkError::Api { code: 502, text }) if text == "Bad Gateway"
+        ));
+    }
+
     #[tokio::test]
     async fn test_auth_flow() {
         let mut plurk = Plurk::new(
@@ -314,5 +1140,6 @@ mod tests {
         let _ = plurk.request_auth().await;
         let _ = plurk.get_auth_url();
         let _ = plurk.verify_auth("1234").await;
+        let _ = plurk.authorize_interactive(|_| {}, async { "1234" }).await;
     }
 }
@@ -0,0 +1,95 @@
+use crate::plurk::Plurk;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Client-side token bucket limiting how fast requests are sent.
+///
+/// Plurk temporarily bans clients that call the API too aggressively. Once installed
+/// with [`Plurk::with_rate_limiter`], every request waits for a token; up to `requests`
+/// tokens are available at once and they refill evenly over `per`. Clones share the
+/// same bucket.
+#[derive(Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    /// Tokens added per second.
+    rate: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    /// Allow `requests` requests every `per`, e.g. `RateLimiter::new(60, Duration::from_secs(60))`.
+    pub fn new(requests: u32, per: Duration) -> Self {
+        let capacity = f64::from(requests.max(1));
+        Self {
+            capacity,
+            rate: capacity / per.as_secs_f64().max(f64::MIN_POSITIVE),
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Take a token and return when the request holding it may be sent. Requests
+    /// arriving while the bucket is empty are queued behind each other.
+    fn reserve_at(&self, now: Instant) -> Instant {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.capacity) - 1.0;
+        bucket.updated = now;
+        if bucket.tokens >= 0.0 {
+            now
+        } else {
+            now + Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+
+    pub(crate) async fn acquire(&self) {
+        tokio::time::sleep_until(self.reserve_at(Instant::now())).await;
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("capacity", &self.capacity)
+            .field("rate", &self.rate)
+            .finish()
+    }
+}
+
+impl Plurk {
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(2));
+        let start = Instant::now();
+        assert_eq!(limiter.reserve_at(start), start);
+        assert_eq!(limiter.reserve_at(start), start);
+        assert_eq!(limiter.reserve_at(start), start + Duration::from_secs(1));
+        assert_eq!(limiter.reserve_at(start), start + Duration::from_secs(2));
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(limiter.reserve_at(later), later);
+        assert_eq!(limiter.clone().reserve_at(later), later);
+        assert_eq!(limiter.reserve_at(later), later + Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::plurk::PlurkError;
+use std::future::Future;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+/// Owner of a bot's background tasks, shutting all of them down with one call.
+///
+/// Every task gets the runtime's [`CancellationToken`]; long-running loops select on
+/// [`CancellationToken::cancelled`] to stop waiting, flush what they hold and return.
+/// [`Self::shutdown`] then cancels the token and awaits every task.
+///
+/// The built-in tasks are started with [`Self::spawn_comet`], which abandons its long
+/// poll and saves the stream offset, [`Self::spawn_outbox`], which posts its queued
+/// plurks, and `spawn_scheduler` with the `scheduler` feature, which stops waiting for
+/// the next job.
+#[derive(Debug, Default)]
+pub struct PlurkRuntime {
+    tasks: JoinSet<Result<(), PlurkError>>,
+    token: CancellationToken,
+}
+
+impl PlurkRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token cancelled on shutdown, for tasks spawned outside the runtime.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Spawn the future returned by `task`, which receives the shutdown token.
+    pub fn spawn<F, Fut>(&mut self, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = Result<(), PlurkError>> + Send + 'static,
+    {
+        self.tasks.spawn(task(self.token.clone()));
+    }
+
+    /// Cancel every task and wait for all of them to finish, returning the errors they
+    /// ended with. A panicking task resumes its panic here.
+    pub async fn shutdown(mut self) -> Vec<PlurkError> {
+        self.token.cancel();
+        let mut errors = Vec::new();
+        while let Some(res) = self.tasks.join_next().await {
+            match res {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => errors.push(e),
+                Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                Err(_) => {}
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let flushed = Arc::new(AtomicBool::new(false));
+        let mut runtime = PlurkRuntime::new();
+        let done = flushed.clone();
+        runtime.spawn(|token| async move {
+            token.cancelled().await;
+            done.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+        runtime.spawn(|_| async { Err(PlurkError::IOError(String::from("foo"))) });
+
+        let errors = runtime.shutdown().await;
+        assert!(flushed.load(Ordering::SeqCst));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "IO Error: foo");
+    }
+}
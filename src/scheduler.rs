@@ -0,0 +1,283 @@
+use crate::models::Qualifier;
+use crate::plurk::{Plurk, PlurkError};
+use crate::runtime::PlurkRuntime;
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use futures::future::BoxFuture;
+use std::{fmt, future::Future, str::FromStr};
+use tokio_util::sync::CancellationToken;
+
+type ContentFn = dyn Fn() -> BoxFuture<'static, Result<String, PlurkError>> + Send + Sync;
+
+struct Job {
+    schedule: Schedule,
+    tz: Tz,
+    qualifier: Qualifier,
+    content: Box<ContentFn>,
+}
+
+/// Resolve a wall clock time of `tz` to an instant.
+///
+/// Times repeated when clocks fall back resolve to their first occurrence, and times
+/// skipped when clocks spring forward to the instant they would have had without the
+/// transition, e.g. 02:30 becomes 03:30 on the day the clocks move from 02:00 to 03:00.
+fn resolve_local(tz: &Tz, local: &NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(local) {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t.with_timezone(&Utc),
+        LocalResult::None => {
+            let before = tz.offset_from_utc_datetime(&(*local - Duration::days(1)));
+            let utc = *local - Duration::seconds(before.fix().local_minus_utc() as i64);
+            Utc.from_utc_datetime(&utc)
+        }
+    }
+}
+
+impl Job {
+    /// The first run strictly after `after`.
+    ///
+    /// The schedule is evaluated on the local wall clock of the job's timezone, so a
+    /// daily 09:00 job keeps firing at 09:00 local time across DST transitions.
+    fn next_after(&self, after: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = Utc.from_utc_datetime(&after.with_timezone(&self.tz).naive_local());
+        self.schedule
+            .after(&local)
+            .map(|wall| resolve_local(&self.tz, &wall.naive_utc()))
+            .find(|t| t > after)
+    }
+}
+
+/// Recurring plurks posted on cron-like schedules.
+///
+/// Expressions use the `sec min hour day-of-month month day-of-week [year]` format of the
+/// [`cron`] crate, e.g. `"0 0 9 * * *"` for every day at 09:00, and are evaluated in
+/// UTC unless a timezone is given with [`Self::add_job_in`].
+pub struct PlurkScheduler {
+    plurk: Plurk,
+    jobs: Vec<Job>,
+}
+
+impl PlurkScheduler {
+    pub fn new(plurk: Plurk) -> Self {
+        Self {
+            plurk,
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Register a job posting the text produced by `content` every time `expression`
+    /// fires. Returns the job index passed to the error handler of [`Self::run`].
+    pub fn add_job<F, Fut>(
+        &mut self,
+        expression: &str,
+        qualifier: Qualifier,
+        content: F,
+    ) -> Result<usize, PlurkError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, PlurkError>> + Send + 'static,
+    {
+        self.add_job_in(expression, Tz::UTC, qualifier, content)
+    }
+
+    /// Like [`Self::add_job`], with `expression` evaluated in `tz`,
+    /// e.g. `chrono_tz::Asia::Taipei`.
+    pub fn add_job_in<F, Fut>(
+        &mut self,
+        expression: &str,
+        tz: Tz,
+        qualifier: Qualifier,
+        content: F,
+    ) -> Result<usize, PlurkError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, PlurkError>> + Send + 'static,
+    {
+        let schedule = Schedule::from_str(expression)
+            .map_err(|e| PlurkError::InvalidArgument(format!("{}: {}", expression, e)))?;
+        self.jobs.push(Job {
+            schedule,
+            tz,
+            qualifier,
+            content: Box::new(move || Box::pin(content())),
+        });
+        Ok(self.jobs.len() - 1)
+    }
+
+    /// The earliest time after `after` at which any job fires, with the jobs due then.
+    pub fn next_run(&self, after: &DateTime<Utc>) -> Option<(DateTime<Utc>, Vec<usize>)> {
+        let upcoming: Vec<Option<DateTime<Utc>>> =
+            self.jobs.iter().map(|job| job.next_after(after)).collect();
+        let next = upcoming.iter().flatten().min().copied()?;
+        let due = upcoming
+            .iter()
+            .enumerate()
+            .filter(|(_, at)| **at == Some(next))
+            .map(|(i, _)| i)
+            .collect();
+        Some((next, due))
+    }
+
+    /// Post for every job as it fires, until no job has a future run.
+    ///
+    /// A failing job does not stop the scheduler; its error is handed to `on_error`
+    /// together with the job index.
+    pub async fn run<E>(&self, on_error: E)
+    where
+        E: FnMut(usize, PlurkError),
+    {
+        self.run_until(CancellationToken::new(), on_error).await
+    }
+
+    /// Like [`Self::run`], returning once `token` is cancelled. Jobs already firing are
+    /// posted before returning.
+    pub async fn run_until<E>(&self, token: CancellationToken, mut on_error: E)
+    where
+        E: FnMut(usize, PlurkError),
+    {
+        let mut last = Utc::now();
+        while let Some((next, due)) = self.next_run(&last) {
+            let wait = (next - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = token.cancelled() => return,
+                _ = tokio::time::sleep(wait) => {}
+            }
+            for i in due {
+                if let Err(e) = self.run_job(&self.jobs[i]).await {
+                    on_error(i, e);
+                }
+            }
+            last = next;
+        }
+    }
+
+    async fn run_job(&self, job: &Job) -> Result<(), PlurkError> {
+        let content = (job.content)().await?;
+        self.plurk
+            .add_plurk(&content, job.qualifier.clone())
+            .await?;
+        Ok(())
+    }
+}
+
+impl PlurkRuntime {
+    /// Run `scheduler` until the runtime shuts down.
+    pub fn spawn_scheduler<E>(&mut self, scheduler: PlurkScheduler, on_error: E)
+    where
+        E: FnMut(usize, PlurkError) + Send + 'static,
+    {
+        self.spawn(move |token| async move {
+            scheduler.run_until(token, on_error).await;
+            Ok(())
+        });
+    }
+}
+
+impl fmt::Debug for PlurkScheduler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PlurkScheduler")
+            .field("plurk", &self.plurk)
+            .field("jobs", &self.jobs.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America, Asia};
+
+    #[test]
+    fn test_next_run() {
+        let mut scheduler = PlurkScheduler::new(Plurk::new("key", "secret", None, None));
+        let content = || async { Ok(String::from("hello")) };
+        assert_eq!(
+            scheduler
+                .add_job("0 0 9 * * *", Qualifier::Freestyle, content)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            scheduler
+                .add_job("0 0 * * * *", Qualifier::Freestyle, content)
+                .unwrap(),
+            1
+        );
+        assert!(scheduler
+            .add_job("every day", Qualifier::Freestyle, content)
+            .is_err());
+
+        let at = |h, m| Utc.with_ymd_and_hms(2023, 5, 1, h, m, 0).unwrap();
+        assert_eq!(scheduler.next_run(&at(7, 30)), Some((at(8, 0), vec![1])));
+        assert_eq!(scheduler.next_run(&at(8, 0)), Some((at(9, 0), vec![0, 1])));
+    }
+
+    #[test]
+    fn test_next_run_empty() {
+        let scheduler = PlurkScheduler::new(Plurk::new("key", "secret", None, None));
+        assert_eq!(scheduler.next_run(&Utc::now()), None);
+    }
+
+    #[test]
+    fn test_next_run_in_timezone() {
+        let mut scheduler = PlurkScheduler::new(Plurk::new("key", "secret", None, None));
+        let content = || async { Ok(String::from("hello")) };
+        scheduler
+            .add_job_in("0 0 9 * * *", Asia::Taipei, Qualifier::Freestyle, content)
+            .unwrap();
+
+        let after = Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap();
+        let next = Utc.with_ymd_and_hms(2023, 5, 1, 1, 0, 0).unwrap();
+        assert_eq!(scheduler.next_run(&after), Some((next, vec![0])));
+    }
+
+    #[test]
+    fn test_dst_transitions() {
+        let mut scheduler = PlurkScheduler::new(Plurk::new("key", "secret", None, None));
+        let content = || async { Ok(String::from("hello")) };
+        scheduler
+            .add_job_in(
+                "0 30 1,2 * * *",
+                America::New_York,
+                Qualifier::Freestyle,
+                content,
+            )
+            .unwrap();
+        let runs = |from: DateTime<Utc>, n| {
+            let mut runs = Vec::new();
+            let mut last = from;
+            for _ in 0..n {
+                last = scheduler.next_run(&last).unwrap().0;
+                runs.push(last);
+            }
+            runs
+        };
+        let utc = |d, h, m| Utc.with_ymd_and_hms(2023, 3, d, h, m, 0).unwrap();
+
+        // 02:30 does not exist on 2023-03-12 and runs at 03:30 EDT instead.
+        assert_eq!(
+            runs(utc(12, 0, 0), 3),
+            [utc(12, 6, 30), utc(12, 7, 30), utc(13, 5, 30)]
+        );
+
+        // 01:30 happens twice on 2023-11-05 and only the first one runs.
+        let utc = |d, h, m| Utc.with_ymd_and_hms(2023, 11, d, h, m, 0).unwrap();
+        assert_eq!(
+            runs(utc(5, 0, 0), 3),
+            [utc(5, 5, 30), utc(5, 7, 30), utc(6, 6, 30)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_scheduler() {
+        let mut scheduler = PlurkScheduler::new(Plurk::new("key", "secret", None, None));
+        let content = || async { Ok(String::from("hello")) };
+        scheduler
+            .add_job("0 0 0 1 1 *", Qualifier::Freestyle, content)
+            .unwrap();
+
+        let mut runtime = PlurkRuntime::new();
+        runtime.spawn_scheduler(scheduler, |_, _| {});
+        assert!(runtime.shutdown().await.is_empty());
+    }
+}
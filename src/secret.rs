@@ -16,10 +16,39 @@ impl fmt::Display for SecretError {
     }
 }
 
+/// Wrapper for secret values whose `Debug` output never shows the value.
+///
+/// Serialization is transparent; use [`Redacted::expose`] to read the value.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Redacted<T>(T);
+
+impl<T> Redacted<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct SecretPair {
     key: String,
-    secret: String,
+    secret: Redacted<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -41,12 +70,12 @@ impl Secret {
         Self {
             consumer: SecretPair {
                 key: consumer_key.into(),
-                secret: consumer_secret.into(),
+                secret: Redacted::new(consumer_secret.into()),
             },
             token: if let (Some(key), Some(secret)) = (token_key, token_secret) {
                 Some(SecretPair {
                     key: key.into(),
-                    secret: secret.into(),
+                    secret: Redacted::new(secret.into()),
                 })
             } else {
                 None
@@ -62,7 +91,7 @@ impl Secret {
             consumer: self.consumer,
             token: Some(SecretPair {
                 key: token_key.into(),
-                secret: token_secret.into(),
+                secret: Redacted::new(token_secret.into()),
             }),
         }
     }
@@ -73,7 +102,7 @@ impl Secret {
     {
         self.token = Some(SecretPair {
             key: token_key.into(),
-            secret: token_secret.into(),
+            secret: Redacted::new(token_secret.into()),
         });
     }
 
@@ -82,18 +111,18 @@ impl Secret {
     }
 
     pub fn get_token_key(&self) -> Option<String> {
-        if let Some(token) = &self.token {
-            Some(token.key.clone())
-        } else {
-            None
-        }
+        self.token.as_ref().map(|token| token.key.clone())
     }
 
     pub fn get_sign_secret(&self) -> String {
         if let Some(token) = &self.token {
-            format!("{}&{}", self.consumer.secret, token.secret)
+            format!(
+                "{}&{}",
+                self.consumer.secret.expose(),
+                token.secret.expose()
+            )
         } else {
-            format!("{}&", self.consumer.secret)
+            format!("{}&", self.consumer.secret.expose())
         }
     }
 
@@ -122,13 +151,17 @@ impl fmt::Display for Secret {
             write!(
                 f,
                 "Consumer Key: {}\nConsumer Secret: {}\nToken Key: {}\nToken Secret: {}",
-                self.consumer.key, self.consumer.secret, token.key, token.secret,
+                self.consumer.key,
+                self.consumer.secret.expose(),
+                token.key,
+                token.secret.expose(),
             )
         } else {
             write!(
                 f,
                 "Consumer Key: {}\nConsumer Secret: {}",
-                self.consumer.key, self.consumer.secret,
+                self.consumer.key,
+                self.consumer.secret.expose(),
             )
         }
     }
@@ -191,6 +224,15 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_debug_redacted() {
+        let secret = Secret::new("c1", "c2", None, None).update_token("t1", "t2");
+        let res = format!("{:?}", secret);
+        assert!(res.contains("c1") && res.contains("t1"));
+        assert!(!res.contains("c2") && !res.contains("t2"));
+        assert!(res.contains("[REDACTED]"));
+    }
+
     #[test]
     fn test_error() {
         let res = format!("{}", SecretError::IOError(String::from("abc")));
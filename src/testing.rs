@@ -0,0 +1,261 @@
+//! Helpers for testing code built on this crate without reaching Plurk.
+//!
+//! [`MockServer`] answers requests in process from canned responses such as those in
+//! [`fixtures`], and records what was sent:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), rust_plurk::plurk::PlurkError> {
+//! use rust_plurk::testing::{fixtures, MockServer};
+//!
+//! let server = MockServer::new().mock("/APP/Users/me", fixtures::USERS_ME);
+//! let me = server.plurk().me().await?;
+//! assert_eq!(me.nick_name, "alvin");
+//! assert_eq!(server.requests()[0].path, "/APP/Users/me");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::plurk::{Plurk, PlurkError};
+use crate::transport::PlurkTransport;
+use futures::future::BoxFuture;
+use reqwest::{Method, Request, Response};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Example responses of common endpoints, shaped like those of the live API.
+pub mod fixtures {
+    /// `/APP/Users/me`
+    pub const USERS_ME: &str = r#"{
+        "id": 3, "nick_name": "alvin", "display_name": "Alvin", "full_name": "Alvin Kuo",
+        "karma": 102.4, "avatar": 5, "has_profile_image": 1, "gender": 1,
+        "location": "Taipei, Taiwan", "date_of_birth": "Sat, 19 Mar 1983 00:00:00 GMT",
+        "premium": false, "default_lang": "tr_ch"
+    }"#;
+
+    /// `/APP/Timeline/getPlurk`
+    pub const GET_PLURK: &str = r#"{
+        "plurk": {
+            "plurk_id": 1495754904, "owner_id": 3, "user_id": 3,
+            "content": "hello <b>world</b>", "content_raw": "hello **world**",
+            "qualifier": "says", "posted": "Fri, 05 Jun 2009 23:07:13 GMT",
+            "response_count": 1, "is_unread": 0, "lang": "en", "plurk_type": 0,
+            "no_comments": 0, "limited_to": null, "favorite": false, "favorite_count": 2,
+            "replurked": false, "replurkers_count": 0, "replurkable": true
+        },
+        "user": {"id": 3, "nick_name": "alvin", "display_name": "Alvin", "has_profile_image": 0}
+    }"#;
+
+    /// `/APP/Timeline/getPlurks`
+    pub const GET_PLURKS: &str = r#"{
+        "plurks": [{
+            "plurk_id": 1495754904, "owner_id": 3, "user_id": 3,
+            "content": "hello <b>world</b>", "content_raw": "hello **world**",
+            "qualifier": "says", "posted": "Fri, 05 Jun 2009 23:07:13 GMT",
+            "response_count": 1, "is_unread": 0, "lang": "en", "plurk_type": 0,
+            "no_comments": 0, "limited_to": null
+        }, {
+            "plurk_id": 1495754903, "owner_id": 5, "user_id": 5,
+            "content": "morning", "content_raw": "morning",
+            "qualifier": "feels", "posted": "Fri, 05 Jun 2009 22:00:00 GMT",
+            "response_count": 0, "is_unread": 1, "lang": "en", "plurk_type": 1,
+            "no_comments": 2, "limited_to": "|3||5|"
+        }],
+        "plurk_users": {
+            "3": {"id": 3, "nick_name": "alvin", "has_profile_image": 0},
+            "5": {"id": 5, "nick_name": "amber", "has_profile_image": 1, "avatar": 2}
+        }
+    }"#;
+
+    /// `/APP/Timeline/plurkAdd`
+    pub const PLURK_ADD: &str = r#"{
+        "plurk_id": 1495754905, "owner_id": 3, "user_id": 3,
+        "content": "hi", "content_raw": "hi", "qualifier": "says",
+        "posted": "Sat, 06 Jun 2009 08:00:00 GMT", "response_count": 0, "is_unread": 0,
+        "lang": "en", "plurk_type": 0, "no_comments": 0, "limited_to": null
+    }"#;
+
+    /// `/APP/Responses/get`
+    pub const RESPONSES_GET: &str = r#"{
+        "responses": [{
+            "id": 7, "user_id": 5, "plurk_id": 1495754904,
+            "content": "nice", "content_raw": "nice", "qualifier": ":",
+            "posted": "Fri, 05 Jun 2009 23:10:00 GMT", "lang": "en"
+        }],
+        "response_count": 1, "responses_seen": 0,
+        "friends": {"5": {"id": 5, "nick_name": "amber", "has_profile_image": 0}}
+    }"#;
+
+    /// The `success_text` answer of endpoints without a result, e.g. `plurkDelete`.
+    pub const SUCCESS: &str = r#"{"success_text": "ok"}"#;
+
+    /// An `error_text` answer, sent with status 400.
+    pub const PLURK_NOT_FOUND: &str = r#"{"error_text": "Plurk not found"}"#;
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockRequest {
+    pub method: Method,
+    /// Path of the URL, e.g. `/APP/Users/me`.
+    pub path: String,
+    /// Parameters of the query string followed by those of a form body.
+    pub params: Vec<(String, String)>,
+    /// Whether the request carried an OAuth `Authorization` header.
+    pub signed: bool,
+}
+
+impl MockRequest {
+    /// The first value of the parameter `name`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Path -> (status, body)
+    routes: HashMap<String, (u16, String)>,
+    requests: Vec<MockRequest>,
+}
+
+/// An in-process stand-in for the Plurk API, answering by URL path.
+///
+/// Paths without a mock get a 404 with an `error_text`. Clones share the mocks and the
+/// recorded requests.
+#[derive(Debug, Clone, Default)]
+pub struct MockServer {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `path` with status 200 and `body`.
+    pub fn mock<S: Into<String>>(self, path: &str, body: S) -> Self {
+        self.mock_status(path, 200, body)
+    }
+
+    /// Answer `path` with `status` and `body`, replacing an earlier mock of `path`.
+    pub fn mock_status<S: Into<String>>(self, path: &str, status: u16, body: S) -> Self {
+        self.lock()
+            .routes
+            .insert(path.to_string(), (status, body.into()));
+        self
+    }
+
+    /// An authorized client sending its requests to this server.
+    pub fn plurk(&self) -> Plurk {
+        Plurk::builder("key", "secret")
+            .token("token", "token_secret")
+            .transport(self.clone())
+            .build()
+            .expect("a client without TLS or proxy settings builds")
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn respond(&self, request: &Request) -> (u16, String) {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .unwrap_or_default();
+        let params = url::form_urlencoded::parse(request.url().query().unwrap_or("").as_bytes())
+            .chain(url::form_urlencoded::parse(body))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        let path = request.url().path().to_string();
+
+        let mut state = self.lock();
+        state.requests.push(MockRequest {
+            method: request.method().clone(),
+            path: path.clone(),
+            params,
+            signed: request
+                .headers()
+                .contains_key(reqwest::header::AUTHORIZATION),
+        });
+        state.routes.get(&path).cloned().unwrap_or_else(|| {
+            let text = serde_json::json!({ "error_text": format!("No mock for {}", path) });
+            (404, text.to_string())
+        })
+    }
+}
+
+impl PlurkTransport for MockServer {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, PlurkError>> {
+        let (status, body) = self.respond(&request);
+        let res = http::Response::builder()
+            .status(status)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .map_err(|e| PlurkError::InvalidArgument(e.to_string()));
+        Box::pin(async move { res.map(Response::from) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::timeline::TimelineOptions;
+
+    #[tokio::test]
+    async fn test_mock_server() {
+        let server = MockServer::new()
+            .mock("/APP/Users/me", fixtures::USERS_ME)
+            .mock("/APP/Timeline/getPlurk", fixtures::GET_PLURK)
+            .mock("/APP/Timeline/getPlurks", fixtures::GET_PLURKS)
+            .mock("/APP/Responses/get", fixtures::RESPONSES_GET);
+        let plurk = server.plurk();
+
+        assert_eq!(plurk.me().await.unwrap().name(), "Alvin");
+        let post = plurk.get_plurk(1_495_754_904).await.unwrap();
+        assert_eq!(post.plurk.text(), "hello world");
+        let plurks = plurk.get_plurks(TimelineOptions::default()).await.unwrap();
+        assert_eq!(plurks.len(), 2);
+        let page = plurk.get_responses(1_495_754_904, 0).await.unwrap();
+        assert_eq!(page.friends[&5].nick_name, "amber");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests.iter().all(|r| r.signed));
+        assert_eq!(requests[1].path, "/APP/Timeline/getPlurk");
+        assert_eq!(requests[1].param("plurk_id"), Some("1495754904"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_server_errors() {
+        let server =
+            MockServer::new().mock_status("/APP/Timeline/getPlurk", 400, fixtures::PLURK_NOT_FOUND);
+        let plurk = server.plurk();
+
+        let e = plurk.get_plurk(3).await.unwrap_err();
+        assert!(matches!(e, PlurkError::Api { code: 400, .. }));
+        let e = plurk.me().await.unwrap_err();
+        assert!(matches!(e, PlurkError::Api { code: 404, .. }));
+    }
+
+    #[test]
+    fn test_fixtures() {
+        use crate::models::{PlurkLang, PlurkPost, PlurkUser};
+
+        let user: PlurkUser = serde_json::from_str(fixtures::USERS_ME).unwrap();
+        assert!(user.date_of_birth.is_some());
+        assert_eq!(user.default_lang, Some(PlurkLang::TraditionalChinese));
+        let post: PlurkPost = serde_json::from_str(fixtures::PLURK_ADD).unwrap();
+        assert_eq!(post.posted.timestamp(), 1_244_275_200);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::plurk::PlurkError;
+use futures::future::BoxFuture;
+use reqwest::{Request, Response};
+use std::{fmt, sync::Arc};
+
+/// The HTTP layer used by [`Plurk`](crate::plurk::Plurk) to send its signed requests, `reqwest::Client` by
+/// default. Set with [`PlurkBuilder::transport`](crate::plurk::PlurkBuilder::transport).
+///
+/// A fake transport lets tests exercise signing, parsing and error handling without
+/// network access; responses can be built with `reqwest::Response::from(http::Response)`.
+pub trait PlurkTransport: Send + Sync {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, PlurkError>>;
+}
+
+impl PlurkTransport for reqwest::Client {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, PlurkError>> {
+        Box::pin(async move {
+            reqwest::Client::execute(self, request)
+                .await
+                .map_err(PlurkError::ReqwestError)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Transport(Arc<dyn PlurkTransport>);
+
+impl Transport {
+    pub(crate) fn new<T>(transport: T) -> Self
+    where
+        T: PlurkTransport + 'static,
+    {
+        Self(Arc::new(transport))
+    }
+
+    pub(crate) async fn execute(&self, request: Request) -> Result<Response, PlurkError> {
+        self.0.execute(request).await
+    }
+}
+
+impl fmt::Debug for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Transport")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PlurkUser;
+    use crate::plurk::Plurk;
+    use std::sync::Mutex;
+
+    /// Answers every request with `status` and `body`, recording what was sent.
+    struct Fake {
+        status: u16,
+        body: &'static str,
+        sent: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl PlurkTransport for Fake {
+        fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, PlurkError>> {
+            self.sent.lock().unwrap().push(request);
+            let res = http::Response::builder()
+                .status(self.status)
+                .body(self.body)
+                .unwrap();
+            Box::pin(async move { Ok(Response::from(res)) })
+        }
+    }
+
+    fn plurk(status: u16, body: &'static str) -> (Plurk, Arc<Mutex<Vec<Request>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let fake = Fake {
+            status,
+            body,
+            sent: sent.clone(),
+        };
+        let plurk = Plurk::builder("key", "secret")
+            .token("token", "token_secret")
+            .transport(fake)
+            .build()
+            .unwrap();
+        (plurk, sent)
+    }
+
+    #[tokio::test]
+    async fn test_fake_transport() {
+        let (plurk, sent) = plurk(200, r#"{"id": 3, "nick_name": "alvin"}"#);
+        let user: PlurkUser = plurk
+            .request_json("/APP/Users/me", None::<()>, None::<(String, String)>)
+            .await
+            .unwrap();
+        assert_eq!(user.nick_name, "alvin");
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent[0].url().as_str(), "https://www.plurk.com/APP/Users/me");
+        let auth = sent[0].headers()[reqwest::header::AUTHORIZATION]
+            .to_str()
+            .unwrap();
+        assert!(auth.starts_with("OAuth "));
+        assert!(auth.contains("oauth_token=\"token\""));
+        assert!(auth.contains("oauth_signature="));
+    }
+
+    #[tokio::test]
+    async fn test_fake_transport_error() {
+        let (plurk, _) = plurk(400, r#"{"error_text": "Plurk not found"}"#);
+        let res = plurk
+            .get("/APP/Timeline/getPlurk", Some([("plurk_id", 3)]))
+            .await;
+        match res {
+            Err(PlurkError::Api { code, text }) => {
+                assert_eq!(code, 400);
+                assert_eq!(text, "Plurk not found");
+            }
+            _ => panic!("expected an API error"),
+        }
+    }
+}